@@ -1,17 +1,152 @@
-const socket = io("https://clearlang.org/");
+// ?server=http://localhost:3000 points the game at a self-hosted speedy_jumper_server
+const leaderboard_server = new URLSearchParams(window.location.search).get("server") || "https://clearlang.org/";
+// socket.io retries on its own, with the same backoff as the desktop build
+const socket = io(leaderboard_server, { transports: ["websocket"], reconnectionDelay: 1000, reconnectionDelayMax: 30000 });
+
+// must match PROTOCOL_VERSION in src/client/message.rs
+const LEADERBOARD_PROTOCOL_VERSION = 4;
+
+// servers that send whole entries repeat them as update_player for older pages, those repeats are skipped
+let server_sends_entries = false;
+
+// hands a leaderboard message to the game, see Message in src/client/message.rs
+function leaderboard_receive(message) {
+    message.version = LEADERBOARD_PROTOCOL_VERSION;
+    wasm_exports._leaderboard_receive(js_object(JSON.stringify(message)));
+}
 
 register_plugin = function (importObject) {    
-    importObject.env._register_name = function (js_object) {
-        socket.emit('player_name', consume_js_object(js_object));
+    importObject.env._leaderboard_send = function (js_object) {
+        const message = JSON.parse(consume_js_object(js_object));
+
+        if (message.version !== LEADERBOARD_PROTOCOL_VERSION) {
+            leaderboard_receive({ type: "error", message: "plugin.js speaks protocol " + LEADERBOARD_PROTOCOL_VERSION + ", got " + message.version });
+            return;
+        }
+
+        switch (message.type) {
+            case "join":
+                if (!socket.connected) {
+                    leaderboard_receive({ type: "join_rejected", key: message.key, reason: "offline" });
+                    break;
+                }
+
+                // servers that never answer are timed out by the game, which then plays the run unconfirmed
+                socket.emit('join', { name: message.name, key: message.key, token: message.token || null });
+                break;
+            case "submit":
+                socket.emit('player_time', [message.key, message.time]);
+                break;
+            case "finish":
+                socket.emit('run_finished', { key: message.key, time: message.time, seed: message.seed });
+                break;
+            case "leave":
+                socket.emit('player_time', [message.key, -1]);
+                break;
+            default:
+                leaderboard_receive({ type: "error", message: "plugin.js can't send " + message.type });
+        }
+    }
+
+    importObject.env._storage_write = function (name, data) {
+        const key = "speedy_jumper/" + consume_js_object(name);
+        const bytes = consume_js_object(data);
+
+        let binary = "";
+        for (let i = 0; i < bytes.length; i++) {
+            binary += String.fromCharCode(bytes[i]);
+        }
+
+        localStorage.setItem(key, btoa(binary));
+    }
+
+    importObject.env._storage_read = function (name) {
+        const value = localStorage.getItem("speedy_jumper/" + consume_js_object(name));
+
+        if (value === null) {
+            return -1;
+        }
+
+        const binary = atob(value);
+        const bytes = new Uint8Array(binary.length);
+        for (let i = 0; i < binary.length; i++) {
+            bytes[i] = binary.charCodeAt(i);
+        }
+
+        return js_object(bytes);
     }
 
-    importObject.env._register_time = function (name, time) {
-        socket.emit('player_time', [consume_js_object(name), time]);
+    importObject.env._gamepad_poll = function () {
+        const pads = navigator.getGamepads ? navigator.getGamepads() : [];
+
+        for (const pad of pads) {
+            if (pad === null || !pad.connected) {
+                continue;
+            }
+
+            let buttons = 0;
+            pad.buttons.forEach((button, i) => {
+                if (button.pressed && i < 32) {
+                    buttons |= 1 << i;
+                }
+            });
+
+            return js_object({ x: pad.axes[0] || 0, y: pad.axes[1] || 0, buttons: buttons >>> 0 });
+        }
+
+        return -1;
     }
 }
 
+// the server replays the whole board after every connect, so start from an empty one
+socket.on('connect', () => {
+    server_sends_entries = false;
+    leaderboard_receive({ type: "snapshot", entries: {} });
+    leaderboard_receive({ type: "status", status: "online" });
+});
+
+socket.on('connect_error', (error) => {
+    leaderboard_receive({ type: "error", message: "failed to connect to " + leaderboard_server + ": " + error.message });
+    leaderboard_receive({ type: "status", status: "offline" });
+});
+
+socket.on('disconnect', () => {
+    leaderboard_receive({ type: "status", status: "offline" });
+});
+
+socket.io.on('reconnect_attempt', () => {
+    leaderboard_receive({ type: "status", status: "reconnecting" });
+});
+
+socket.on('join_accepted', (answer) => {
+    leaderboard_receive({ type: "join_accepted", key: answer.key, token: answer.token });
+});
+
+socket.on('join_rejected', (answer) => {
+    leaderboard_receive({ type: "join_rejected", key: answer.key, reason: answer.reason });
+});
+
+socket.on('entry', (update) => {
+    server_sends_entries = true;
+    leaderboard_receive({ type: "entry", key: update.key, entry: update.entry });
+});
+
+// a negative time means the player left
 socket.on('update_player', (player) => {
-    wasm_exports._update_player(js_object(player[0]), player[1]);
+    if (player[1] < 0) {
+        leaderboard_receive({ type: "leave", key: player[0] });
+    } else if (!server_sends_entries) {
+        leaderboard_receive({ type: "submit", key: player[0], time: player[1] });
+    }
 });
 
+function on_focus_change() {
+    if (document.hidden || !document.hasFocus()) {
+        wasm_exports._window_focus_lost();
+    }
+}
+
+document.addEventListener("visibilitychange", on_focus_change);
+window.addEventListener("blur", on_focus_change);
+
 miniquad_add_plugin({register_plugin});
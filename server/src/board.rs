@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value, json};
+
+// how long runs restored from disk wait for their player to reconnect and submit again
+pub const RESTORED_GRACE: Duration = Duration::from_secs(60);
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+pub type ConnectionId = u64;
+
+struct FinishedRun {
+    time: f64,
+    timestamp: f64,
+    date: String,
+    seed: Option<String>
+}
+
+struct Entry {
+    best: Option<FinishedRun>,
+    runs: u64,
+    // (time, timestamp) of the run in progress
+    live: Option<(f64, f64)>,
+    // None once the connection is gone, or for entries restored from disk that nobody has claimed yet
+    owner: Option<ConnectionId>
+}
+
+impl Entry {
+    // the better of the best finished run and the run in progress
+    fn time(&self) -> f64 {
+        match (&self.best, self.live) {
+            (Some(best), Some((time, _))) => best.time.max(time),
+            (Some(best), None) => best.time,
+            (None, Some((time, _))) => time,
+            (None, None) => 0.0
+        }
+    }
+
+    // what players see, the same shape as the game's LeaderboardEntry
+    fn to_json(&self, key: &str) -> Value {
+        let mut entry = match (&self.best, self.live) {
+            (Some(best), Some((time, _))) if best.time >= time => finished_json(best),
+            (_, Some((time, timestamp))) => json!({ "time": time, "timestamp": timestamp, "date": utc_date(timestamp) }),
+            (Some(best), None) => finished_json(best),
+            (None, None) => json!({ "time": 0.0, "timestamp": 0.0, "date": "" })
+        };
+
+        entry["mode"] = Value::from(mode_of(key));
+        entry["live"] = Value::from(self.live.is_some());
+        entry["runs"] = Value::from(self.runs);
+
+        entry
+    }
+
+    fn is_empty(&self) -> bool {
+        self.best.is_none() && self.live.is_none()
+    }
+}
+
+fn finished_json(best: &FinishedRun) -> Value {
+    let mut entry = json!({ "time": best.time, "timestamp": best.timestamp, "date": best.date });
+
+    if let Some(seed) = &best.seed {
+        entry["seed"] = Value::from(seed.as_str());
+    }
+
+    entry
+}
+
+// The board: every key's best finished run, its run count and the time of its run in progress.
+// Keys without a finished run are removed when their run is left or their connection drops.
+pub struct Board {
+    entries: BTreeMap<String, Entry>,
+    path: PathBuf,
+    restored_at: Instant,
+    dirty: bool
+}
+
+impl Board {
+    // a missing file starts an empty board, a corrupt one is reported and not overwritten
+    pub fn load(path: PathBuf) -> Result<Board, String> {
+        let mut entries = BTreeMap::new();
+
+        if path.exists() {
+            let text = fs::read_to_string(&path).map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+            let saved: BTreeMap<String, Value> = serde_json::from_str(&text)
+                .map_err(|error| format!("failed to parse {}: {error}", path.display()))?;
+
+            for (key, value) in saved {
+                let entry = parse_saved(&value).ok_or_else(|| format!("malformed entry {key} in {}", path.display()))?;
+
+                entries.insert(key, entry);
+            }
+        }
+
+        Ok(Board {
+            entries,
+            path,
+            restored_at: Instant::now(),
+            dirty: false
+        })
+    }
+
+    // hands an existing entry to the connection that joined as its key
+    pub fn claim(&mut self, key: &str, connection: ConnectionId) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.owner = Some(connection);
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    // the time older clients show and the whole entry, None once the key is gone
+    pub fn entry(&self, key: &str) -> Option<(f64, Value)> {
+        self.entries.get(key).map(|entry| (entry.time(), entry.to_json(key)))
+    }
+
+    // Returns whether the board changed, a negative time ends the run in progress.
+    // Only called for keys the connection joined, see Sessions::may_submit.
+    pub fn submit(&mut self, connection: ConnectionId, key: &str, time: f64) -> bool {
+        if !time.is_finite() {
+            return false;
+        }
+
+        if time < 0.0 {
+            return self.end_live_run(key);
+        }
+
+        let entry = self.entries.entry(key.to_owned()).or_insert(Entry { best: None, runs: 0, live: None, owner: None });
+
+        entry.live = Some((time, now()));
+        entry.owner = Some(connection);
+        self.dirty = true;
+
+        true
+    }
+
+    // records a finished run, returns whether the board changed, same as `submit` only for joined keys
+    pub fn finish(&mut self, connection: ConnectionId, key: &str, time: f64, seed: Option<String>) -> bool {
+        if !time.is_finite() || time < 0.0 {
+            return false;
+        }
+
+        let entry = self.entries.entry(key.to_owned()).or_insert(Entry { best: None, runs: 0, live: None, owner: None });
+
+        entry.live = None;
+        entry.runs += 1;
+        entry.owner = Some(connection);
+
+        if entry.best.as_ref().is_none_or(|best| best.time < time) {
+            let timestamp = now();
+
+            entry.best = Some(FinishedRun { time, timestamp, date: utc_date(timestamp), seed });
+        }
+
+        self.dirty = true;
+
+        true
+    }
+
+    // ends the connection's runs, returns the changed keys
+    pub fn disconnect(&mut self, connection: ConnectionId) -> Vec<String> {
+        self.release_where(|entry| entry.owner == Some(connection))
+    }
+
+    // ends restored runs once their grace period is over, returns the changed keys
+    pub fn expire_unclaimed(&mut self) -> Vec<String> {
+        if self.restored_at.elapsed() < RESTORED_GRACE {
+            return Vec::new();
+        }
+
+        self.release_where(|entry| entry.owner.is_none() && entry.live.is_some())
+    }
+
+    // writes the board if it changed since the last save
+    pub fn save(&mut self) -> Result<(), String> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let saved: Map<String, Value> = self.entries.iter()
+            .map(|(key, entry)| (key.clone(), saved_json(entry)))
+            .collect();
+
+        let text = serde_json::to_string_pretty(&saved).map_err(|error| error.to_string())?;
+
+        // written next to the target first so a crash mid-write keeps the previous board
+        let temporary = self.path.with_extension("tmp");
+
+        fs::write(&temporary, text).map_err(|error| format!("failed to write {}: {error}", temporary.display()))?;
+        fs::rename(&temporary, &self.path).map_err(|error| format!("failed to replace {}: {error}", self.path.display()))?;
+
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    fn end_live_run(&mut self, key: &str) -> bool {
+        let Some(entry) = self.entries.get_mut(key) else {
+            return false;
+        };
+
+        if entry.live.take().is_none() {
+            return false;
+        }
+
+        if entry.is_empty() {
+            self.entries.remove(key);
+        }
+
+        self.dirty = true;
+
+        true
+    }
+
+    fn release_where(&mut self, predicate: impl Fn(&Entry) -> bool) -> Vec<String> {
+        let released: Vec<String> = self.entries.iter()
+            .filter(|(_, entry)| predicate(entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &released {
+            if let Some(entry) = self.entries.get_mut(key) {
+                entry.owner = None;
+            }
+
+            self.end_live_run(key);
+        }
+
+        released
+    }
+}
+
+// {"best": {time, timestamp, date, seed}, "runs": n, "live": time}
+fn parse_saved(value: &Value) -> Option<Entry> {
+    let best = match &value["best"] {
+        Value::Null => None,
+        best => Some(FinishedRun {
+            time: best["time"].as_f64()?,
+            timestamp: best["timestamp"].as_f64()?,
+            date: best["date"].as_str()?.to_owned(),
+            seed: best["seed"].as_str().map(str::to_owned)
+        })
+    };
+
+    let live = value["live"].as_f64().map(|time| (time, now()));
+
+    Some(Entry { best, runs: value["runs"].as_u64().unwrap_or(0), live, owner: None })
+}
+
+fn saved_json(entry: &Entry) -> Value {
+    json!({
+        "best": entry.best.as_ref().map(finished_json),
+        "runs": entry.runs,
+        "live": entry.live.map(|(time, _)| time)
+    })
+}
+
+// the board title in the key's tag, same as the game's leaderboard_key where untagged keys are on Normal
+fn mode_of(key: &str) -> &str {
+    match key.strip_suffix(']').and_then(|rest| rest.rsplit_once(" [")) {
+        Some((_, tag)) => tag,
+        None => "Normal"
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+// YYYY-MM-DD, the same days to civil date conversion as the game's calendar::utc_date, both are tested on the same days
+fn utc_date(timestamp: f64) -> String {
+    let z = (timestamp / SECONDS_PER_DAY) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the same days as the game's calendar tests, the two conversions have to agree
+    const KNOWN_DAYS: [(u64, &str); 5] = [
+        (0, "1970-01-01"),
+        (10956, "1999-12-31"),
+        (11016, "2000-02-29"),
+        (20744, "2026-10-18"),
+        (47541, "2100-03-01")
+    ];
+
+    #[test]
+    fn timestamps_convert_to_the_games_dates() {
+        for (day, date) in KNOWN_DAYS {
+            let midnight = day as f64 * SECONDS_PER_DAY;
+
+            assert_eq!(utc_date(midnight), date);
+            assert_eq!(utc_date(midnight + SECONDS_PER_DAY - 1.0), date);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// Leaderboard server for Speedy Jumper, speaking the socket.io protocol the game's plugin.js uses.
+//
+//   speedy_jumper_server [--bind ADDRESS] [--data FILE]
+
+mod board;
+mod protocol;
+mod server;
+mod sessions;
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use board::*;
+use server::*;
+
+const DEFAULT_BIND: &str = "127.0.0.1:3000";
+const DEFAULT_DATA: &str = "leaderboard.json";
+
+struct Options {
+    bind: String,
+    data: PathBuf
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        bind: DEFAULT_BIND.to_owned(),
+        data: PathBuf::from(DEFAULT_DATA)
+    };
+
+    let mut arguments = std::env::args().skip(1);
+
+    while let Some(argument) = arguments.next() {
+        let mut value = || arguments.next().ok_or(format!("{argument} needs a value"));
+
+        match argument.as_str() {
+            "--bind" => options.bind = value()?,
+            "--data" => options.data = PathBuf::from(value()?),
+            _ => return Err(format!("unknown argument {argument}"))
+        }
+    }
+
+    Ok(options)
+}
+
+fn start() -> Result<Server, String> {
+    let options = parse_options()?;
+    let board = Board::load(options.data)?;
+
+    Server::bind(&options.bind, board)
+}
+
+fn main() -> ExitCode {
+    let server = match start() {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match server.local_address() {
+        Ok(address) => println!("listening on {address}"),
+        Err(error) => eprintln!("{error}")
+    }
+
+    let _ = std::io::stdout().flush();
+
+    server.run();
+
+    ExitCode::SUCCESS
+}
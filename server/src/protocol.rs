@@ -0,0 +1,115 @@
+// The subset of Engine.IO v4 and socket.io v5 the game uses, over the websocket transport only:
+//   "0{...}"  engine open, sent by the server right after the websocket handshake
+//   "2" / "3" engine ping from the server and the client's pong
+//   "40"      socket.io connect to the default namespace, answered with "40{\"sid\":...}"
+//   "42[...]" socket.io event, a JSON array of the event name followed by its arguments
+//
+// Events from the game: join {name, key, token}, player_time [key, time], run_finished {key, time, seed}
+// and the older player_name name.
+// Events from the server: join_accepted {key, token}, join_rejected {key, reason}, entry {key, entry}
+// and update_player [key, time], which older games read instead of entries.
+
+use serde_json::{Value, json};
+
+pub const PING_INTERVAL_MS: u64 = 25000;
+pub const PING_TIMEOUT_MS: u64 = 20000;
+pub const MAX_PAYLOAD: usize = 100_000;
+
+pub enum Incoming {
+    Pong,
+    Close,
+    Connect,
+    Disconnect,
+    Event(String, Vec<Value>),
+    // packets that need no answer, e.g. noop or acks
+    Ignored
+}
+
+pub fn open_packet(sid: &str) -> String {
+    let open = json!({
+        "sid": sid,
+        "upgrades": [],
+        "pingInterval": PING_INTERVAL_MS,
+        "pingTimeout": PING_TIMEOUT_MS,
+        "maxPayload": MAX_PAYLOAD
+    });
+
+    format!("0{open}")
+}
+
+pub fn ping_packet() -> String {
+    "2".to_owned()
+}
+
+pub fn connect_packet(sid: &str) -> String {
+    format!("40{}", json!({ "sid": sid }))
+}
+
+pub fn event_packet(name: &str, arguments: Vec<Value>) -> String {
+    let mut array = vec![Value::from(name)];
+    array.extend(arguments);
+
+    format!("42{}", Value::Array(array))
+}
+
+// a negative time removes the key
+pub fn update_packet(key: &str, time: f64) -> String {
+    event_packet("update_player", vec![json!([key, time])])
+}
+
+// the whole entry of a key still on the board
+pub fn entry_packet(key: &str, entry: Value) -> String {
+    event_packet("entry", vec![json!({ "key": key, "entry": entry })])
+}
+
+pub fn parse(text: &str) -> Result<Incoming, String> {
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some('1') => Ok(Incoming::Close),
+        Some('3') => Ok(Incoming::Pong),
+        Some('4') => parse_socket_packet(chars.as_str()),
+        Some('2' | '5' | '6') => Ok(Incoming::Ignored),
+        Some(other) => Err(format!("unknown engine packet type '{other}'")),
+        None => Err("empty packet".to_owned())
+    }
+}
+
+fn parse_socket_packet(text: &str) -> Result<Incoming, String> {
+    let mut chars = text.chars();
+    let kind = chars.next().ok_or("empty socket.io packet")?;
+    let rest = chars.as_str();
+
+    // only the default namespace exists, anything addressed elsewhere is dropped
+    if rest.starts_with('/') && !rest.starts_with("/,") {
+        return Ok(Incoming::Ignored);
+    }
+
+    let rest = rest.strip_prefix("/,").unwrap_or(rest);
+    // the ack id, which the game never asks for
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+
+    match kind {
+        '0' => Ok(Incoming::Connect),
+        '1' => Ok(Incoming::Disconnect),
+        '2' => {
+            let value: Value = serde_json::from_str(rest).map_err(|error| format!("malformed event: {error}"))?;
+
+            let Value::Array(mut array) = value else {
+                return Err("event is not an array".to_owned());
+            };
+
+            if array.is_empty() {
+                return Err("event has no name".to_owned());
+            }
+
+            let Value::String(name) = array.remove(0) else {
+                return Err("event name is not a string".to_owned());
+            };
+
+            Ok(Incoming::Event(name, array))
+        }
+        '3' | '6' => Ok(Incoming::Ignored),
+        other => Err(format!("unsupported socket.io packet type '{other}'"))
+    }
+}
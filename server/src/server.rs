@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+use tungstenite::{Message, WebSocket};
+
+use crate::board::*;
+use crate::protocol::*;
+use crate::sessions::*;
+
+// how often connection threads look for outgoing packets while waiting on the socket
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Shared {
+    board: Board,
+    sessions: Sessions,
+    // outgoing packets of every connection that finished the socket.io connect
+    connections: HashMap<ConnectionId, Sender<String>>
+}
+
+impl Shared {
+    // sends everyone the key's entry as it is now on the board
+    fn broadcast(&mut self, key: &str) {
+        let packets = board_packets(&self.board, key);
+
+        // a failed send means the connection is closing, its thread removes it
+        for sender in self.connections.values() {
+            for packet in &packets {
+                let _ = sender.send(packet.clone());
+            }
+        }
+    }
+
+    fn broadcast_all(&mut self, keys: Vec<String>) {
+        for key in keys {
+            self.broadcast(&key);
+        }
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    shared: Arc<Mutex<Shared>>
+}
+
+impl Server {
+    pub fn bind(address: &str, board: Board) -> Result<Server, String> {
+        let listener = TcpListener::bind(address).map_err(|error| format!("failed to bind {address}: {error}"))?;
+
+        Ok(Server {
+            listener,
+            shared: Arc::new(Mutex::new(Shared {
+                board,
+                sessions: Sessions::default(),
+                connections: HashMap::new()
+            }))
+        })
+    }
+
+    pub fn local_address(&self) -> Result<String, String> {
+        self.listener.local_addr()
+            .map(|address| address.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn run(self) {
+        let shared = self.shared.clone();
+        thread::spawn(move || maintain(shared));
+
+        for (id, stream) in (1..).zip(self.listener.incoming()) {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    eprintln!("failed to accept a connection: {error}");
+                    continue;
+                }
+            };
+
+            let shared = self.shared.clone();
+
+            thread::spawn(move || {
+                if let Err(error) = handle_connection(stream, id, &shared) {
+                    eprintln!("connection {id}: {error}");
+                }
+
+                let mut shared = shared.lock().unwrap();
+                shared.connections.remove(&id);
+
+                shared.sessions.disconnect(id);
+
+                let changed = shared.board.disconnect(id);
+                shared.broadcast_all(changed);
+            });
+        }
+    }
+}
+
+// saves the board and expires restored entries in the background
+fn maintain(shared: Arc<Mutex<Shared>>) {
+    loop {
+        thread::sleep(SAVE_INTERVAL);
+
+        let mut shared = shared.lock().unwrap();
+
+        let changed = shared.board.expire_unclaimed();
+        shared.broadcast_all(changed);
+
+        shared.sessions.expire();
+
+        if let Err(error) = shared.board.save() {
+            eprintln!("{error}");
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, id: ConnectionId, shared: &Mutex<Shared>) -> Result<(), String> {
+    let mut socket = tungstenite::accept(stream).map_err(|error| format!("websocket handshake failed: {error}"))?;
+
+    // set after the handshake, which can't be resumed after a timeout
+    socket.get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|error| error.to_string())?;
+
+    let sid = session_id(id);
+    send(&mut socket, open_packet(&sid))?;
+
+    let (sender, receiver) = channel();
+    // handed to the board once the client connects to the namespace
+    let mut sender = Some(sender);
+
+    let mut last_ping = Instant::now();
+    let mut awaiting_pong = false;
+
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let incoming = match parse(text.as_str()) {
+                    Ok(incoming) => incoming,
+                    Err(error) => {
+                        eprintln!("connection {id}: {error}");
+                        continue;
+                    }
+                };
+
+                match incoming {
+                    Incoming::Pong => awaiting_pong = false,
+                    Incoming::Close | Incoming::Disconnect => return Ok(()),
+                    Incoming::Connect => {
+                        let Some(sender) = sender.take() else {
+                            continue;
+                        };
+
+                        // registered before answering, so nothing broadcast after the player sees the connect is missed;
+                        // everyone already on the board goes out first, live updates follow through the channel
+                        let board: Vec<String> = {
+                            let mut shared = shared.lock().unwrap();
+                            shared.connections.insert(id, sender);
+
+                            shared.board.keys().flat_map(|key| board_packets(&shared.board, key)).collect()
+                        };
+
+                        send(&mut socket, connect_packet(&sid))?;
+
+                        for packet in board {
+                            send(&mut socket, packet)?;
+                        }
+                    }
+                    Incoming::Event(name, arguments) => {
+                        if let Some(reply) = handle_event(id, &name, arguments, shared) {
+                            send(&mut socket, reply)?;
+                        }
+                    }
+                    Incoming::Ignored => {}
+                }
+            }
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => return Ok(()),
+            Err(error) => return Err(error.to_string())
+        }
+
+        while let Ok(packet) = receiver.try_recv() {
+            send(&mut socket, packet)?;
+        }
+
+        if awaiting_pong && last_ping.elapsed() > Duration::from_millis(PING_TIMEOUT_MS) {
+            return Err("ping timed out".to_owned());
+        }
+
+        if !awaiting_pong && last_ping.elapsed() > Duration::from_millis(PING_INTERVAL_MS) {
+            send(&mut socket, ping_packet())?;
+
+            last_ping = Instant::now();
+            awaiting_pong = true;
+        }
+    }
+}
+
+// returns the packet to answer the sender with, if any
+fn handle_event(id: ConnectionId, name: &str, arguments: Vec<Value>, shared: &Mutex<Shared>) -> Option<String> {
+    match name {
+        // {name, key, token}, answered with join_accepted {key, token} or join_rejected {key, reason}
+        "join" => {
+            let request = arguments.first()?;
+            let (Some(player), Some(key)) = (request["name"].as_str(), request["key"].as_str()) else {
+                eprintln!("connection {id}: malformed join");
+                return None;
+            };
+
+            let mut shared = shared.lock().unwrap();
+
+            match shared.sessions.join(id, player, key, request["token"].as_str()) {
+                Ok(token) => {
+                    shared.board.claim(key, id);
+                    eprintln!("connection {id} joined as {key}");
+
+                    Some(event_packet("join_accepted", vec![json!({ "key": key, "token": token })]))
+                }
+                Err(reason) => Some(event_packet("join_rejected", vec![json!({ "key": key, "reason": reason })]))
+            }
+        }
+        // sent by games from before joins existed, claims the untagged key of the name if nobody holds it
+        "player_name" => {
+            let Some(Value::String(player)) = arguments.first() else {
+                eprintln!("connection {id}: malformed player_name");
+                return None;
+            };
+
+            let mut shared = shared.lock().unwrap();
+
+            match shared.sessions.join(id, player, player, None) {
+                Ok(_) => {
+                    shared.board.claim(player, id);
+                    eprintln!("connection {id} joined as {player} without a session");
+                }
+                Err(reason) => eprintln!("connection {id} can't use {player}: {reason}")
+            }
+
+            None
+        }
+        // sent as a single [key, time] array by the game
+        "player_time" => {
+            let values = match arguments.as_slice() {
+                [Value::Array(values)] => values.as_slice(),
+                values => values
+            };
+
+            let [Value::String(key), Value::Number(time)] = values else {
+                eprintln!("connection {id}: malformed player_time");
+                return None;
+            };
+
+            let time = time.as_f64()?;
+            let mut shared = shared.lock().unwrap();
+
+            if shared.sessions.may_submit(id, key) && shared.board.submit(id, key, time) {
+                shared.broadcast(key);
+            }
+
+            None
+        }
+        // {key, time, seed}, the seed as text
+        "run_finished" => {
+            let finished = arguments.first()?;
+            let (Some(key), Some(time)) = (finished["key"].as_str(), finished["time"].as_f64()) else {
+                eprintln!("connection {id}: malformed run_finished");
+                return None;
+            };
+
+            let seed = finished["seed"].as_str().map(str::to_owned);
+            let mut shared = shared.lock().unwrap();
+
+            if shared.sessions.may_submit(id, key) && shared.board.finish(id, key, time, seed) {
+                shared.broadcast(key);
+            }
+
+            None
+        }
+        _ => {
+            eprintln!("connection {id}: unknown event {name}");
+            None
+        }
+    }
+}
+
+// the entry first, so games that read entries ignore the bare time after it; a key no longer on the board is removed
+fn board_packets(board: &Board, key: &str) -> Vec<String> {
+    match board.entry(key) {
+        Some((time, entry)) => vec![entry_packet(key, entry), update_packet(key, time)],
+        None => vec![update_packet(key, -1.0)]
+    }
+}
+
+fn send(socket: &mut WebSocket<TcpStream>, packet: String) -> Result<(), String> {
+    socket.send(Message::text(packet)).map_err(|error| error.to_string())
+}
+
+fn session_id(id: ConnectionId) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or(0);
+
+    format!("{id:x}{nanos:08x}")
+}
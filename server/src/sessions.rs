@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::board::ConnectionId;
+
+// how long a dropped player's name stays reserved for their session token
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(60);
+
+// same rules as the game's validate_name
+pub const MAX_NAME_LENGTH: usize = 16;
+
+struct Session {
+    token: String,
+    // None once the connection dropped
+    connection: Option<ConnectionId>,
+    released_at: Instant
+}
+
+// Who holds which key. A key is claimed by joining, and stays with its session token for a while after the
+// connection drops so the same player can take it back.
+#[derive(Default)]
+pub struct Sessions {
+    by_key: HashMap<String, Session>
+}
+
+impl Sessions {
+    // the session token on success, otherwise the reason sent back to the player
+    pub fn join(&mut self, connection: ConnectionId, name: &str, key: &str, token: Option<&str>) -> Result<String, &'static str> {
+        validate_name(name)?;
+
+        let tagged = key.strip_prefix(name).is_some_and(|tag| tag.is_empty() || (tag.starts_with(" [") && tag.ends_with(']')));
+
+        if !tagged {
+            return Err("invalid_characters");
+        }
+
+        if let Some(session) = self.by_key.get_mut(key) {
+            let resumed = token.is_some_and(|token| token == session.token);
+            let free = match session.connection {
+                Some(holder) => holder == connection,
+                None => session.released_at.elapsed() >= RECONNECT_GRACE
+            };
+
+            if resumed || session.connection == Some(connection) {
+                session.connection = Some(connection);
+                return Ok(session.token.clone());
+            }
+
+            if !free {
+                return Err("taken");
+            }
+        }
+
+        let token = new_token(connection);
+
+        self.by_key.insert(key.to_owned(), Session {
+            token: token.clone(),
+            connection: Some(connection),
+            released_at: Instant::now()
+        });
+
+        Ok(token)
+    }
+
+    // only the connection holding the key's session may write to it
+    pub fn may_submit(&self, connection: ConnectionId, key: &str) -> bool {
+        self.by_key.get(key).is_some_and(|session| session.connection == Some(connection))
+    }
+
+    pub fn disconnect(&mut self, connection: ConnectionId) {
+        for session in self.by_key.values_mut() {
+            if session.connection == Some(connection) {
+                session.connection = None;
+                session.released_at = Instant::now();
+            }
+        }
+    }
+
+    // forgets released sessions whose grace period is over
+    pub fn expire(&mut self) {
+        self.by_key.retain(|_, session| session.connection.is_some() || session.released_at.elapsed() < RECONNECT_GRACE);
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        return Err("empty");
+    }
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err("too_long");
+    }
+
+    if name.chars().any(|c| c.is_control() || c == '[' || c == ']') {
+        return Err("invalid_characters");
+    }
+
+    Ok(())
+}
+
+// unguessable enough for a game: two independently keyed SipHashes of the connection and the time
+fn new_token(connection: ConnectionId) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+
+    let mut halves = [0u64; 2];
+
+    for half in &mut halves {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(connection);
+        hasher.write_u128(nanos);
+
+        *half = hasher.finish();
+    }
+
+    format!("{:016x}{:016x}", halves[0], halves[1])
+}
@@ -0,0 +1,467 @@
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+use tungstenite::{Message, WebSocket};
+
+const READ_TIMEOUT: Duration = Duration::from_secs(3);
+
+static NEXT_DATA_FILE: AtomicUsize = AtomicUsize::new(0);
+
+struct Instance {
+    process: Child,
+    address: String
+}
+
+impl Instance {
+    fn start(data: &PathBuf) -> Instance {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_speedy_jumper_server"))
+            .args(["--bind", "127.0.0.1:0", "--data"])
+            .arg(data)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to start the server");
+
+        let mut line = String::new();
+        BufReader::new(process.stdout.take().unwrap()).read_line(&mut line).unwrap();
+
+        let address = line.trim()
+            .strip_prefix("listening on ")
+            .expect("server did not report its address")
+            .to_owned();
+
+        Instance { process, address }
+    }
+
+    fn connect(&self) -> Player {
+        let stream = TcpStream::connect(&self.address).unwrap();
+        stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+
+        let url = format!("ws://{}/socket.io/?EIO=4&transport=websocket", self.address);
+        let (socket, _) = tungstenite::client(url, stream).unwrap();
+
+        let mut player = Player { socket };
+
+        let open = player.read();
+        assert!(open.starts_with('0'), "expected an open packet, got {open}");
+
+        player.send("40");
+
+        let connected = player.read();
+        assert!(connected.starts_with("40{"), "expected a connect packet, got {connected}");
+
+        player
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+struct Player {
+    socket: WebSocket<TcpStream>
+}
+
+impl Player {
+    fn send(&mut self, packet: &str) {
+        self.socket.send(Message::text(packet)).unwrap();
+    }
+
+    // the reply: ("join_accepted", token) or ("join_rejected", reason)
+    fn join(&mut self, name: &str, key: &str, token: Option<&str>) -> (String, String) {
+        self.send(&format!("42{}", serde_json::json!(["join", { "name": name, "key": key, "token": token }])));
+
+        let packet = self.read();
+        let event: Value = serde_json::from_str(packet.strip_prefix("42").expect("expected an event")).unwrap();
+
+        assert_eq!(event[1]["key"], key);
+
+        let detail = if event[0] == "join_accepted" { &event[1]["token"] } else { &event[1]["reason"] };
+
+        (event[0].as_str().unwrap().to_owned(), detail.as_str().unwrap().to_owned())
+    }
+
+    fn emit_name(&mut self, name: &str) {
+        self.send(&format!("42{}", serde_json::json!(["player_name", name])));
+    }
+
+    fn emit_time(&mut self, key: &str, time: f64) {
+        self.send(&format!("42{}", serde_json::json!(["player_time", [key, time]])));
+    }
+
+    fn emit_finish(&mut self, key: &str, time: f64, seed: &str) {
+        self.send(&format!("42{}", serde_json::json!(["run_finished", { "key": key, "time": time, "seed": seed }])));
+    }
+
+    // next text packet, answering pings on the way
+    fn read(&mut self) -> String {
+        loop {
+            match self.socket.read().expect("no packet from the server") {
+                Message::Text(text) if text.as_str() == "2" => self.send("3"),
+                Message::Text(text) => return text.as_str().to_owned(),
+                _ => {}
+            }
+        }
+    }
+
+    // the next event of the given name, skipping the others
+    fn read_event(&mut self, name: &str) -> Value {
+        loop {
+            let packet = self.read();
+            let mut event: Value = serde_json::from_str(packet.strip_prefix("42").expect("expected an event")).unwrap();
+
+            if event[0] == name {
+                return event[1].take();
+            }
+        }
+    }
+
+    // the bare times older games read
+    fn read_update(&mut self) -> (String, f64) {
+        let update = self.read_event("update_player");
+
+        (update[0].as_str().unwrap().to_owned(), update[1].as_f64().unwrap())
+    }
+
+    fn read_entry(&mut self) -> (String, Value) {
+        let mut entry = self.read_event("entry");
+
+        (entry["key"].as_str().unwrap().to_owned(), entry["entry"].take())
+    }
+}
+
+fn data_file() -> PathBuf {
+    let index = NEXT_DATA_FILE.fetch_add(1, Ordering::SeqCst);
+    let path = std::env::temp_dir().join(format!("speedy_jumper_server_test_{}_{index}.json", std::process::id()));
+
+    let _ = std::fs::remove_file(&path);
+
+    path
+}
+
+#[test]
+fn handshake_follows_engine_io_v4() {
+    let server = Instance::start(&data_file());
+
+    let stream = TcpStream::connect(&server.address).unwrap();
+    stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+
+    let (socket, _) = tungstenite::client(format!("ws://{}/socket.io/?EIO=4&transport=websocket", server.address), stream).unwrap();
+    let mut player = Player { socket };
+
+    let open: Value = serde_json::from_str(player.read().strip_prefix('0').unwrap()).unwrap();
+
+    assert!(open["sid"].is_string());
+    assert_eq!(open["upgrades"], serde_json::json!([]));
+    assert!(open["pingInterval"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn times_are_broadcast_to_every_player() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut bob = server.connect();
+
+    alice.join("alice", "alice", None);
+    alice.emit_time("alice", 5.5);
+
+    assert_eq!(bob.read_update(), ("alice".to_owned(), 5.5));
+    assert_eq!(alice.read_update(), ("alice".to_owned(), 5.5));
+}
+
+#[test]
+fn negative_time_removes_the_entry() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut bob = server.connect();
+
+    alice.join("alice", "alice", None);
+    alice.emit_time("alice", 3.0);
+    alice.emit_time("alice", -1.0);
+
+    assert_eq!(bob.read_update(), ("alice".to_owned(), 3.0));
+    assert_eq!(bob.read_update(), ("alice".to_owned(), -1.0));
+
+    // a late joiner gets nothing for alice, so its first update is its own
+    let mut carol = server.connect();
+    carol.join("carol", "carol", None);
+    carol.emit_time("carol", 1.0);
+
+    assert_eq!(carol.read_update(), ("carol".to_owned(), 1.0));
+}
+
+#[test]
+fn new_players_receive_the_current_board() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+
+    alice.join("alice", "alice", None);
+    alice.emit_time("alice", 7.25);
+    alice.read_update();
+
+    let mut bob = server.connect();
+
+    assert_eq!(bob.read_update(), ("alice".to_owned(), 7.25));
+}
+
+#[test]
+fn a_dropped_connection_removes_its_entries() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut bob = server.connect();
+
+    alice.join("alice", "alice [Hard]", None);
+    alice.emit_time("alice [Hard]", 2.0);
+    assert_eq!(bob.read_update(), ("alice [Hard]".to_owned(), 2.0));
+
+    drop(alice);
+
+    assert_eq!(bob.read_update(), ("alice [Hard]".to_owned(), -1.0));
+}
+
+#[test]
+fn a_held_key_cannot_be_overwritten_by_another_player() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut mallory = server.connect();
+
+    alice.join("alice", "alice", None);
+    alice.emit_time("alice", 4.0);
+    assert_eq!(mallory.read_update(), ("alice".to_owned(), 4.0));
+
+    mallory.join("mallory", "mallory", None);
+    mallory.emit_time("alice", 100.0);
+    mallory.emit_time("alice", -1.0);
+    mallory.emit_time("mallory", 1.0);
+
+    assert_eq!(mallory.read_update(), ("mallory".to_owned(), 1.0));
+}
+
+#[test]
+fn the_board_survives_a_restart() {
+    let data = data_file();
+
+    {
+        let server = Instance::start(&data);
+        let mut alice = server.connect();
+
+        alice.join("alice", "alice", None);
+        alice.emit_time("alice", 9.0);
+        alice.read_update();
+
+        // the board is written about once a second
+        thread::sleep(Duration::from_millis(1500));
+    }
+
+    let server = Instance::start(&data);
+    let mut bob = server.connect();
+
+    assert_eq!(bob.read_update(), ("alice".to_owned(), 9.0));
+
+    let _ = std::fs::remove_file(&data);
+}
+
+#[test]
+fn malformed_packets_are_ignored() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+
+    alice.join("alice", "alice", None);
+    alice.send("42not json");
+    alice.send("42[\"player_time\",[\"alice\",\"fast\"]]");
+    alice.send("9");
+    alice.emit_time("alice", 1.5);
+
+    assert_eq!(alice.read_update(), ("alice".to_owned(), 1.5));
+}
+
+#[test]
+fn joins_are_confirmed_with_a_session_token() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+
+    let (answer, token) = alice.join("alice", "alice [Hard]", None);
+
+    assert_eq!(answer, "join_accepted");
+    assert_eq!(token.len(), 32);
+}
+
+#[test]
+fn a_joined_name_is_taken_for_everyone_else() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut impostor = server.connect();
+
+    assert_eq!(alice.join("alice", "alice", None).0, "join_accepted");
+    assert_eq!(impostor.join("alice", "alice", None), ("join_rejected".to_owned(), "taken".to_owned()));
+    assert_eq!(impostor.join("alice", "alice", Some("0123456789abcdef0123456789abcdef")).0, "join_rejected");
+
+    // the same name on another board is a different key
+    assert_eq!(impostor.join("alice", "alice [Easy]", None).0, "join_accepted");
+}
+
+#[test]
+fn invalid_names_are_rejected() {
+    let server = Instance::start(&data_file());
+    let mut player = server.connect();
+
+    let rejected = |reason: &str| ("join_rejected".to_owned(), reason.to_owned());
+
+    assert_eq!(player.join(" ", " ", None), rejected("empty"));
+    assert_eq!(player.join("a name that is far too long", "a name that is far too long", None), rejected("too_long"));
+    assert_eq!(player.join("al]ice", "al]ice", None), rejected("invalid_characters"));
+    assert_eq!(player.join("alice", "bob", None), rejected("invalid_characters"));
+}
+
+#[test]
+fn a_reconnecting_player_keeps_their_name_with_their_token() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut bob = server.connect();
+
+    let (_, token) = alice.join("alice", "alice", None);
+    alice.emit_time("alice", 2.0);
+    assert_eq!(bob.read_update(), ("alice".to_owned(), 2.0));
+
+    drop(alice);
+    assert_eq!(bob.read_update(), ("alice".to_owned(), -1.0));
+
+    assert_eq!(bob.join("alice", "alice", None).0, "join_rejected");
+
+    let mut alice = server.connect();
+
+    assert_eq!(alice.join("alice", "alice", Some(&token)), ("join_accepted".to_owned(), token));
+}
+
+#[test]
+fn only_the_joined_player_can_submit_for_a_key() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut mallory = server.connect();
+
+    alice.join("alice", "alice", None);
+    mallory.join("mallory", "mallory", None);
+
+    mallory.emit_time("alice", 100.0);
+    mallory.emit_time("mallory", 1.0);
+
+    assert_eq!(mallory.read_update(), ("mallory".to_owned(), 1.0));
+}
+
+#[test]
+fn keys_nobody_joined_cannot_be_submitted_or_finished() {
+    let server = Instance::start(&data_file());
+    let mut mallory = server.connect();
+
+    mallory.emit_time("alice", 100.0);
+    mallory.emit_finish("alice", 100.0, "1");
+    mallory.emit_time("mallory", 1.0);
+    mallory.join("mallory", "mallory", None);
+    mallory.emit_time("mallory", 2.0);
+
+    assert_eq!(mallory.read_update(), ("mallory".to_owned(), 2.0));
+}
+
+#[test]
+fn older_games_claim_their_name_with_player_name() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut mallory = server.connect();
+
+    alice.emit_name("alice");
+    alice.emit_time("alice", 5.5);
+
+    assert_eq!(mallory.read_update(), ("alice".to_owned(), 5.5));
+
+    // a held name isn't handed to the next player_name
+    mallory.emit_name("alice");
+    mallory.emit_time("alice", 100.0);
+    mallory.emit_name("mallory");
+    mallory.emit_time("mallory", 1.0);
+
+    assert_eq!(mallory.read_update(), ("mallory".to_owned(), 1.0));
+    assert_eq!(alice.read_update(), ("alice".to_owned(), 5.5));
+    assert_eq!(alice.read_update(), ("mallory".to_owned(), 1.0));
+}
+
+#[test]
+fn finished_runs_are_sent_as_whole_entries() {
+    let server = Instance::start(&data_file());
+    let mut alice = server.connect();
+    let mut bob = server.connect();
+
+    alice.join("alice", "alice [Hard]", None);
+    alice.emit_time("alice [Hard]", 3.0);
+
+    let (key, entry) = bob.read_entry();
+
+    assert_eq!(key, "alice [Hard]");
+    assert_eq!(entry["mode"], "Hard");
+    assert_eq!(entry["live"], true);
+    assert_eq!(entry["runs"], 0);
+
+    alice.emit_finish("alice [Hard]", 4.5, "18446744073709551615");
+
+    let (_, entry) = bob.read_entry();
+
+    assert_eq!(entry["time"], 4.5);
+    assert_eq!(entry["live"], false);
+    assert_eq!(entry["runs"], 1);
+    assert_eq!(entry["seed"], "18446744073709551615");
+    assert_eq!(entry["date"].as_str().unwrap().len(), "2026-10-18".len());
+    assert!(entry["timestamp"].as_f64().unwrap() > 0.0);
+
+    // a slower run counts but keeps the best one
+    alice.emit_finish("alice [Hard]", 2.0, "7");
+
+    let (_, entry) = bob.read_entry();
+
+    assert_eq!(entry["time"], 4.5);
+    assert_eq!(entry["runs"], 2);
+    assert_eq!(entry["seed"], "18446744073709551615");
+}
+
+#[test]
+fn finished_runs_stay_after_their_player_leaves() {
+    let data = data_file();
+
+    {
+        let server = Instance::start(&data);
+        let mut alice = server.connect();
+        let mut bob = server.connect();
+
+        alice.join("alice", "alice", None);
+        alice.emit_finish("alice", 6.0, "42");
+        bob.read_entry();
+
+        alice.emit_time("alice", 1.0);
+        alice.emit_time("alice", -1.0);
+        drop(alice);
+
+        // leaving the next run only ends it, the finished one stays
+        assert_eq!(bob.read_update(), ("alice".to_owned(), 6.0));
+        assert_eq!(bob.read_update(), ("alice".to_owned(), 6.0));
+
+        thread::sleep(Duration::from_millis(1500));
+    }
+
+    let server = Instance::start(&data);
+    let mut carol = server.connect();
+
+    let (key, entry) = carol.read_entry();
+
+    assert_eq!(key, "alice");
+    assert_eq!(entry["time"], 6.0);
+    assert_eq!(entry["runs"], 1);
+    assert_eq!(entry["live"], false);
+
+    let _ = std::fs::remove_file(&data);
+}
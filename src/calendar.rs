@@ -0,0 +1,54 @@
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+// days since the unix epoch in UTC
+pub fn current_utc_day() -> u64 {
+    utc_day(macroquad::miniquad::date::now())
+}
+
+// the day of a timestamp in seconds since the unix epoch
+pub fn utc_day(timestamp: f64) -> u64 {
+    (timestamp / SECONDS_PER_DAY) as u64
+}
+
+// YYYY-MM-DD, using the days to civil date conversion from Howard Hinnant's date algorithms
+pub fn utc_date(day: u64) -> String {
+    let z = day as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the server's board::utc_date is checked against the same days
+    const KNOWN_DAYS: [(u64, &str); 5] = [
+        (0, "1970-01-01"),
+        (10956, "1999-12-31"),
+        (11016, "2000-02-29"),
+        (20744, "2026-10-18"),
+        (47541, "2100-03-01")
+    ];
+
+    #[test]
+    fn days_convert_to_civil_dates() {
+        for (day, date) in KNOWN_DAYS {
+            assert_eq!(utc_date(day), date);
+        }
+    }
+
+    #[test]
+    fn a_day_lasts_until_midnight_utc() {
+        assert_eq!(utc_day(20744.0 * SECONDS_PER_DAY), 20744);
+        assert_eq!(utc_day(20745.0 * SECONDS_PER_DAY - 1.0), 20744);
+    }
+}
@@ -1,93 +1,882 @@
+use std::collections::{BTreeMap, VecDeque};
+use macroquad::logging::error;
+use macroquad::miniquad::date;
+use once_cell::sync::Lazy;
 use sapp_jsutils::JsObject;
+use serde::{Deserialize, Serialize};
 
-use std::{collections::{BTreeMap}, sync::RwLock};
-use once_cell::sync::Lazy;
+use crate::simulation::Difficulty;
+
+mod message;
+mod live_board;
+mod standings;
+mod record;
+mod memory;
+mod local_file;
+#[cfg(target_arch = "wasm32")]
+mod js_bridge;
+#[cfg(not(target_arch = "wasm32"))]
+mod network;
+
+pub use message::*;
+pub use live_board::*;
+pub use standings::*;
+pub use record::*;
+pub use memory::*;
+pub use local_file::*;
+#[cfg(target_arch = "wasm32")]
+pub use js_bridge::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use network::*;
+
+pub const MAX_NAME_LENGTH: usize = 16;
+
+// a server that hasn't answered a join by then is treated as unreachable, the run is played unconfirmed
+const JOIN_TIMEOUT: f64 = 5.0;
+
+// Where the leaderboard lives. Entries are addressed by key, see `leaderboard_key`.
+pub trait LeaderboardBackend {
+    // Asks to claim `key` for the player called `name`, answered through `join_answer`.
+    // `token` is the session token of an earlier claim, which lets a reconnecting player keep the key.
+    fn join(&mut self, name: &str, key: &str, token: Option<&str>);
+    // the answer to joining `key` once it arrived, Ok holds the session token
+    fn join_answer(&mut self, key: &str) -> Option<Result<String, JoinError>>;
+    // the running time of `key`'s run
+    fn submit(&mut self, key: &str, time: f64);
+    // the final time of `key`'s run and the seed it was played on
+    fn finish(&mut self, key: &str, time: f64, seed: u64);
+    fn leave(&mut self, key: &str);
+    // every entry currently on the board
+    fn snapshot(&self) -> Standings;
+    // the version of the board `snapshot` would return, see `Client::sync`
+    fn version(&self) -> u64;
+    // None for backends that don't talk to a server
+    fn status(&self) -> Option<ConnectionStatus> {
+        None
+    }
+}
+
+// how the link to a leaderboard server stands, the backend keeps retrying on its own while it's down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionStatus {
+    // the first attempt is still under way
+    #[default]
+    Connecting,
+    Online,
+    // waiting for the next attempt
+    Offline,
+    // trying again after the connection was lost
+    Reconnecting
+}
+
+impl ConnectionStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionStatus::Connecting => "Connecting...",
+            ConnectionStatus::Online => "Online",
+            ConnectionStatus::Offline => "Offline, retrying soon",
+            ConnectionStatus::Reconnecting => "Reconnecting..."
+        }
+    }
+}
+
+// why a name can't be used, sent by servers as e.g. "taken"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinError {
+    Empty,
+    TooLong,
+    InvalidCharacters,
+    Taken,
+    Offline
+}
 
+impl JoinError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            JoinError::Empty => "Enter a name first",
+            JoinError::TooLong => "Names can be at most 16 characters long",
+            JoinError::InvalidCharacters => "Names can't contain brackets or control characters",
+            JoinError::Taken => "That name is already taken",
+            JoinError::Offline => "Can't reach the leaderboard server"
+        }
+    }
+}
+
+// Brackets are reserved for the board tag of `leaderboard_key`.
+pub fn validate_name(name: &str) -> Result<(), JoinError> {
+    if name.trim().is_empty() {
+        return Err(JoinError::Empty);
+    }
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(JoinError::TooLong);
+    }
+
+    if name.chars().any(|c| c.is_control() || c == '[' || c == ']') {
+        return Err(JoinError::InvalidCharacters);
+    }
 
-unsafe extern "C" {
-  fn _register_name(name: JsObject);
-  fn _register_time(name: JsObject, time: f64);
+    Ok(())
 }
 
-pub static LEADERBOARD: Lazy<RwLock<BTreeMap<String, f32>>> = Lazy::new(|| RwLock::new(BTreeMap::new()));
+// written by the page through `_leaderboard_receive` on the web, by the network thread on native
+pub static LEADERBOARD: Lazy<LiveBoard> = Lazy::new(LiveBoard::default);
 
 #[unsafe(no_mangle)]
-pub extern "C" fn _update_player(player: JsObject, score: f64) {
-    let mut message = String::new();
-    player.to_string(&mut message);
+pub extern "C" fn _leaderboard_receive(message: JsObject) {
+    let mut text = String::new();
+    message.to_string(&mut text);
 
-    if score < 0.0 {
-        LEADERBOARD.write()
-            .unwrap()
-            .remove(&message);
+    match Message::decode(&text) {
+        Ok(message) => LEADERBOARD.apply(message),
+        Err(error) => error!("dropped a leaderboard message: {error}")
+    }
+}
+
+// The web build always talks to the page's socket.io bridge. Native builds keep their runs in a local file
+// unless SPEEDY_JUMPER_LEADERBOARD names a server, e.g. ws://localhost:3000, or is "memory" to keep them
+// only for the session.
+pub fn default_backend() -> Box<dyn LeaderboardBackend> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Box::new(JsBridgeBackend)
+    }
 
-        return;
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        match std::env::var("SPEEDY_JUMPER_LEADERBOARD").as_deref() {
+            Ok("memory") => Box::new(MemoryBackend::default()),
+            Ok(endpoint) if endpoint.contains("://") => match NetworkBackend::connect(endpoint, &LEADERBOARD) {
+                Ok(backend) => Box::new(backend),
+                Err(error) => {
+                    error!("can't use leaderboard server {endpoint}: {error}");
+                    Box::new(LocalFileBackend::load())
+                }
+            },
+            _ => Box::new(LocalFileBackend::load())
+        }
     }
+}
 
-    LEADERBOARD.write()
-        .unwrap()
-        .insert(message, score as f32);
+// which leaderboard a run is submitted to
+#[derive(Clone, PartialEq)]
+pub enum Board {
+    Standard(Difficulty),
+    // holds the UTC date of the challenge
+    Daily(String)
 }
 
+impl Board {
+    pub fn title(&self) -> String {
+        match self {
+            Board::Standard(difficulty) => difficulty.name().to_owned(),
+            Board::Daily(date) => format!("Daily {date}")
+        }
+    }
+
+    // Normal keeps the bare name so entries from before boards existed stay where they are
+    fn tag(&self) -> Option<String> {
+        match self {
+            Board::Standard(Difficulty::Normal) => None,
+            _ => Some(self.title())
+        }
+    }
+}
+
+// The server only knows names and times, so the boards are kept apart by tagging the name.
+pub fn leaderboard_key(name: &str, board: &Board) -> String {
+    match board.tag() {
+        Some(tag) => format!("{name} [{tag}]"),
+        None => name.to_owned()
+    }
+}
+
+// the title of the board `key` belongs to
+pub fn board_title_of(key: &str) -> String {
+    match key.strip_suffix(']').and_then(|rest| rest.rsplit_once(" [")) {
+        Some((_, tag)) => tag.to_owned(),
+        None => Board::Standard(Difficulty::Normal).title()
+    }
+}
+
+// the player name if the key belongs to `board`
+pub fn name_on_board<'a>(key: &'a str, board: &Board) -> Option<&'a str> {
+    match board.tag() {
+        Some(tag) => key.strip_suffix(&format!(" [{tag}]")),
+        None if key.ends_with(']') && key.contains(" [") => None,
+        None => Some(key)
+    }
+}
+
+// what a join is for, only joins for a run are answered through `Client::poll_join`
+enum JoinPurpose {
+    Run,
+    // claims the current entry again once the server is back
+    Rejoin,
+    Flush(QueuedScore)
+}
+
+struct PendingJoin {
+    name: String,
+    key: String,
+    started: f64,
+    purpose: JoinPurpose
+}
+
+struct Entry {
+    name: String,
+    key: String,
+    // false while the server doesn't know about the entry, e.g. it was joined offline or the connection dropped since
+    confirmed: bool
+}
+
+// the final time of a run that ended while the server couldn't be reached
+struct QueuedScore {
+    name: String,
+    key: String,
+    time: f64,
+    seed: u64
+}
+
+// One player's session on whichever backend was picked at startup.
 pub struct Client {
-    client_name: String,
-    leaderboard: BTreeMap<String, f32>
+    backend: Box<dyn LeaderboardBackend>,
+    pending_join: Option<PendingJoin>,
+    // None while not joined, times are only submitted once the server accepted the join
+    entry: Option<Entry>,
+    // session tokens of every key joined this session, so joining one again keeps it
+    tokens: BTreeMap<String, String>,
+    queued_scores: VecDeque<QueuedScore>,
+    // keys only joined to flush a queued score, they stay on the board until the next leave
+    flushed_keys: Vec<String>,
+    was_online: bool,
+    leaderboard: Standings
 }
 
 impl Client {
-    pub fn empty() -> Client {
+    pub fn new(backend: Box<dyn LeaderboardBackend>) -> Client {
+        let was_online = matches!(backend.status(), None | Some(ConnectionStatus::Online));
+
         Client {
-            client_name: "empty".to_owned(), 
-            leaderboard: BTreeMap::new()
+            backend,
+            pending_join: None,
+            entry: None,
+            tokens: BTreeMap::new(),
+            queued_scores: VecDeque::new(),
+            flushed_keys: Vec::new(),
+            was_online,
+            leaderboard: Standings::default()
         }
     }
 
-    pub fn new(client_name: String) -> Result<Client, String> {
-        if LEADERBOARD.write().unwrap().contains_key(&client_name) {
-            return Err("name already present".to_owned());
+    pub fn status(&self) -> Option<ConnectionStatus> {
+        self.backend.status()
+    }
+
+    fn is_online(&self) -> bool {
+        matches!(self.status(), None | Some(ConnectionStatus::Online))
+    }
+
+    // Starts joining, `poll_join` reports the answer. Names the server would refuse anyway fail right away.
+    pub fn join(&mut self, client_name: &str, board: &Board) -> Result<(), JoinError> {
+        self.leave();
+
+        validate_name(client_name)?;
+
+        let key = leaderboard_key(client_name, board);
+
+        self.start_join(client_name.to_owned(), key, JoinPurpose::Run);
+
+        Ok(())
+    }
+
+    fn start_join(&mut self, name: String, key: String, purpose: JoinPurpose) {
+        // without a connection the run is played offline and joined once the server is back
+        if self.is_online() {
+            self.backend.join(&name, &key, self.tokens.get(&key).map(String::as_str));
         }
 
-        let client = Client {
-            client_name,
-            leaderboard: LEADERBOARD.write().unwrap().clone()
+        self.pending_join = Some(PendingJoin { name, key, started: date::now(), purpose });
+    }
+
+    // None while the answer to the last join is outstanding or nothing is being joined
+    pub fn poll_join(&mut self) -> Option<Result<(), JoinError>> {
+        let pending = self.pending_join.as_ref()?;
+
+        if !matches!(pending.purpose, JoinPurpose::Run) {
+            return None;
+        }
+
+        // servers that never answer joins are played against the same way as no server, the scores are queued
+        let answer = if self.is_online() {
+            match self.backend.join_answer(&pending.key) {
+                Some(Err(JoinError::Offline)) => Ok(None),
+                Some(answer) => answer.map(Some),
+                None if date::now() - pending.started > JOIN_TIMEOUT => Ok(None),
+                None => return None
+            }
+        } else {
+            Ok(None)
         };
 
-        client.register_name();
-        
-        return Ok(client);
-    }
- 
-    pub fn register_name(&self) {
-        if cfg!(target_arch = "wasm32") {
-            unsafe {
-                _register_name(JsObject::string(&self.client_name.as_str()));
+        let PendingJoin { name, key, .. } = self.pending_join.take()?;
+
+        Some(answer.map(|token| {
+            let confirmed = token.is_some();
+
+            if let Some(token) = token {
+                self.tokens.insert(key.clone(), token);
             }
+
+            self.entry = Some(Entry { name, key, confirmed });
+        }))
+    }
+
+    // Called every frame. Rejoins the current entry and flushes queued scores once the server is reachable again.
+    pub fn update(&mut self) {
+        let online = self.is_online();
+
+        // the server forgets who held what when the connection drops
+        if online != self.was_online && let Some(entry) = &mut self.entry {
+            entry.confirmed = false;
+        }
+
+        self.was_online = online;
+
+        if !online {
+            self.abandon_background_join();
+            return;
+        }
+
+        self.poll_background_join();
+
+        if self.pending_join.is_some() {
+            return;
+        }
+
+        if let Some(entry) = self.entry.as_ref().filter(|entry| !entry.confirmed) {
+            self.start_join(entry.name.clone(), entry.key.clone(), JoinPurpose::Rejoin);
+            return;
+        }
+
+        let Some(score) = self.queued_scores.pop_front() else {
+            return;
+        };
+
+        if self.entry.as_ref().is_some_and(|entry| entry.key == score.key) {
+            self.backend.finish(&score.key, score.time, score.seed);
         } else {
-            
+            self.start_join(score.name.clone(), score.key.clone(), JoinPurpose::Flush(score));
         }
     }
 
-    pub fn register_time(&self, time: f64) {
-        if cfg!(target_arch = "wasm32") {
-            unsafe {
-                _register_time(JsObject::string(&self.client_name.as_str()), time);
+    fn poll_background_join(&mut self) {
+        let Some(pending) = &self.pending_join else {
+            return;
+        };
+
+        if matches!(pending.purpose, JoinPurpose::Run) {
+            return;
+        }
+
+        let answer = match self.backend.join_answer(&pending.key) {
+            Some(answer) => answer,
+            // tried again on the next update
+            None if date::now() - pending.started > JOIN_TIMEOUT => return self.abandon_background_join(),
+            None => return
+        };
+
+        let Some(PendingJoin { key, purpose, .. }) = self.pending_join.take() else {
+            return;
+        };
+
+        match answer {
+            Ok(token) => {
+                self.tokens.insert(key.clone(), token);
+
+                match purpose {
+                    JoinPurpose::Rejoin => match &mut self.entry {
+                        Some(entry) if entry.key == key => entry.confirmed = true,
+                        // left while the rejoin was under way
+                        _ => self.backend.leave(&key)
+                    },
+                    JoinPurpose::Flush(score) => {
+                        self.backend.finish(&score.key, score.time, score.seed);
+                        self.flushed_keys.push(score.key);
+                    }
+                    JoinPurpose::Run => {}
+                }
+            }
+            Err(error) => {
+                error!("the leaderboard server refused {key}: {}", error.message());
+
+                if matches!(purpose, JoinPurpose::Rejoin) && self.entry.as_ref().is_some_and(|entry| entry.key == key) {
+                    self.entry = None;
+                }
+
+                self.queued_scores.retain(|score| score.key != key);
             }
+        }
+    }
+
+    // drops an unanswered rejoin or flush, the scores stay queued
+    fn abandon_background_join(&mut self) {
+        if self.pending_join.as_ref().is_some_and(|pending| matches!(pending.purpose, JoinPurpose::Run)) {
+            return;
+        }
+
+        if let Some(PendingJoin { purpose: JoinPurpose::Flush(score), .. }) = self.pending_join.take() {
+            self.queued_scores.push_front(score);
+        }
+    }
+
+    pub fn leave(&mut self) {
+        self.abandon_background_join();
+
+        if self.pending_join.as_ref().is_some_and(|pending| matches!(pending.purpose, JoinPurpose::Run)) {
+            self.pending_join = None;
+        }
+
+        if let Some(entry) = self.entry.take() && entry.confirmed {
+            self.backend.leave(&entry.key);
+        }
+
+        for key in std::mem::take(&mut self.flushed_keys) {
+            self.backend.leave(&key);
+        }
+    }
+
+    // the running time of the current run, dropped while the server can't be reached
+    pub fn register_time(&mut self, time: f64) {
+        if let Some(entry) = &self.entry && entry.confirmed && self.is_online() {
+            self.backend.submit(&entry.key, time);
+        }
+    }
+
+    // the final time of the current run, kept until the server is back if it can't be sent now
+    pub fn finish_run(&mut self, time: f64, seed: u64) {
+        let Some(entry) = &self.entry else {
+            return;
+        };
+
+        if entry.confirmed && self.is_online() {
+            self.backend.finish(&entry.key, time, seed);
         } else {
+            self.queued_scores.push_back(QueuedScore { name: entry.name.clone(), key: entry.key.clone(), time, seed });
+        }
+    }
+
+    // where the joined entry places on `board` and how many entries there are, as of the last sync
+    pub fn rank(&self, board: &Board) -> Option<(usize, usize)> {
+        let key = &self.entry.as_ref()?.key;
 
+        let mut rank = None;
+        let mut count = 0;
+
+        for (other, _) in self.leaderboard.ranked().filter(|(other, _)| name_on_board(other, board).is_some()) {
+            count += 1;
+
+            if other == key {
+                rank = Some(count);
+            }
         }
+
+        rank.map(|rank| (rank, count))
     }
 
+    // the key of the joined entry
+    pub fn key(&self) -> Option<&str> {
+        self.entry.as_ref().map(|entry| entry.key.as_str())
+    }
+
+    // only copies the board when it changed since the last sync
     pub fn sync(&mut self) {
-        self.leaderboard = LEADERBOARD.write().unwrap().clone();
+        if self.backend.version() != self.leaderboard.version() {
+            self.leaderboard = self.backend.snapshot();
+        }
     }
 
-    pub fn get_leaderboard(&self) -> &BTreeMap<String, f32> {
+    pub fn get_leaderboard(&self) -> &Standings {
         &self.leaderboard
     }
 }
 
 impl Drop for Client {
     fn drop(&mut self) {
-        self.register_time(-1.0);
+        self.leave();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn joined(client: &mut Client, name: &str, board: &Board) -> Result<(), JoinError> {
+        client.join(name, board)?;
+        client.poll_join().unwrap()
+    }
+
+    #[test]
+    fn submitted_times_show_up_under_the_board_key() {
+        let mut client = Client::new(Box::new(MemoryBackend::default()));
+        let board = Board::Standard(Difficulty::Hard);
+
+        joined(&mut client, "alice", &board).unwrap();
+        client.register_time(12.5);
+        client.sync();
+
+        let entries: Vec<_> = client.get_leaderboard().ranked().map(|(key, entry)| (key, entry.time, entry.live)).collect();
+
+        assert_eq!(entries, vec![("alice [Hard]", 12.5, true)]);
+        assert_eq!(name_on_board("alice [Hard]", &board), Some("alice"));
+    }
+
+    #[test]
+    fn finished_runs_keep_the_best_time_and_count_runs() {
+        let mut client = Client::new(Box::new(MemoryBackend::default()));
+        let board = Board::Standard(Difficulty::Normal);
+
+        joined(&mut client, "alice", &board).unwrap();
+        client.register_time(3.0);
+        client.finish_run(3.0, 9);
+
+        joined(&mut client, "alice", &board).unwrap();
+        client.register_time(2.0);
+        client.sync();
+
+        let entry = client.get_leaderboard().get("alice").unwrap().clone();
+
+        assert_eq!((entry.time, entry.live, entry.runs, entry.seed), (3.0, true, 1, Some(9)));
+        assert_eq!(entry.mode, "Normal");
+
+        client.finish_run(2.0, 10);
+        client.sync();
+
+        let entry = client.get_leaderboard().get("alice").unwrap();
+
+        assert_eq!((entry.time, entry.live, entry.runs, entry.seed), (3.0, false, 2, Some(9)));
+    }
+
+    #[test]
+    fn the_rank_counts_only_the_current_board() {
+        let mut backend = MemoryBackend::default();
+        backend.finish("bob", 8.0, 1);
+        backend.finish("carol", 2.0, 1);
+        backend.finish("dave [Hard]", 20.0, 1);
+
+        let mut client = Client::new(Box::new(backend));
+        let board = Board::Standard(Difficulty::Normal);
+
+        joined(&mut client, "alice", &board).unwrap();
+        client.finish_run(5.0, 3);
+        client.sync();
+
+        assert_eq!(client.rank(&board), Some((2, 3)));
+
+        client.leave();
+
+        assert_eq!(client.rank(&board), None);
+    }
+
+    #[test]
+    fn keys_name_their_board() {
+        assert_eq!(board_title_of("alice"), "Normal");
+        assert_eq!(board_title_of("alice [Hard]"), "Hard");
+        assert_eq!(board_title_of("alice [Daily 2026-10-18]"), "Daily 2026-10-18");
+    }
+
+    #[test]
+    fn a_held_key_cannot_be_joined_again() {
+        let mut backend = MemoryBackend::default();
+        backend.submit("alice", 3.0);
+
+        let mut client = Client::new(Box::new(backend));
+
+        assert_eq!(joined(&mut client, "alice", &Board::Standard(Difficulty::Normal)), Err(JoinError::Taken));
+        assert_eq!(joined(&mut client, "alice", &Board::Standard(Difficulty::Easy)), Ok(()));
+    }
+
+    #[test]
+    fn names_are_validated_before_asking_the_backend() {
+        let mut client = Client::new(Box::new(MemoryBackend::default()));
+        let board = Board::Standard(Difficulty::Normal);
+
+        assert_eq!(client.join("  ", &board), Err(JoinError::Empty));
+        assert_eq!(client.join("a name that is far too long", &board), Err(JoinError::TooLong));
+        assert_eq!(client.join("alice [Hard]", &board), Err(JoinError::InvalidCharacters));
+        assert!(client.pending_join.is_none());
+    }
+
+    #[test]
+    fn times_wait_for_the_join_to_be_accepted() {
+        let mut client = Client::new(Box::new(NetworklessBackend));
+        let board = Board::Standard(Difficulty::Normal);
+
+        client.join("alice", &board).unwrap();
+        client.register_time(1.0);
+
+        assert!(client.poll_join().is_none());
+        assert!(client.pending_join.is_some());
+
+        LEADERBOARD_FOR_TESTS.apply(Message::JoinAccepted { key: "alice".to_owned(), token: "secret".to_owned() });
+
+        assert_eq!(client.poll_join(), Some(Ok(())));
+        assert_eq!(client.tokens.get("alice").map(String::as_str), Some("secret"));
+    }
+
+    #[test]
+    fn unanswered_joins_play_the_run_unconfirmed() {
+        let mut client = Client::new(Box::new(NetworklessBackend));
+        let board = Board::Standard(Difficulty::Normal);
+
+        client.join("erin", &board).unwrap();
+        client.pending_join.as_mut().unwrap().started -= JOIN_TIMEOUT + 1.0;
+
+        assert_eq!(client.poll_join(), Some(Ok(())));
+
+        client.register_time(1.0);
+        client.finish_run(2.0, 7);
+
+        assert!(client.entry.as_ref().is_some_and(|entry| !entry.confirmed));
+        assert_eq!(client.queued_scores.len(), 1);
+    }
+
+    #[test]
+    fn bridge_messages_update_the_live_board() {
+        let board = LiveBoard::default();
+
+        let finished = LeaderboardEntry {
+            time: 2.0,
+            timestamp: 100.0,
+            date: "2026-10-18".to_owned(),
+            mode: "Normal".to_owned(),
+            live: false,
+            runs: 4,
+            seed: Some(1)
+        };
+
+        board.apply(Message::Submit { key: "stale".to_owned(), time: 1.0 });
+        board.apply(Message::Snapshot { entries: BTreeMap::from([("bob".to_owned(), finished.clone())]) });
+        board.apply(Message::Entry { key: "dave [Hard]".to_owned(), entry: LeaderboardEntry { time: 5.0, ..finished.clone() } });
+        board.apply(Message::Submit { key: "carol".to_owned(), time: 3.0 });
+        board.apply(Message::Submit { key: "bob".to_owned(), time: 1.5 });
+        board.apply(Message::Leave { key: "dave [Hard]".to_owned() });
+
+        let standings = board.standings();
+        let entries: Vec<_> = standings.ranked().map(|(key, entry)| (key, entry.time, entry.live, entry.runs)).collect();
+
+        // times from servers that only report times count as running
+        assert_eq!(entries, vec![("carol", 3.0, true, 0), ("bob", 1.5, true, 4)]);
+    }
+
+    #[test]
+    fn live_times_do_not_move_the_board_layout() {
+        let board = LiveBoard::default();
+
+        board.apply(Message::Submit { key: "bob".to_owned(), time: 9.0 });
+        board.apply(Message::Submit { key: "alice".to_owned(), time: 1.0 });
+
+        let layout_version = board.standings().layout_version();
+
+        for tick in 1..100 {
+            board.apply(Message::Submit { key: "alice".to_owned(), time: 1.0 + tick as f64 / 120.0 });
+        }
+
+        assert_eq!(board.standings().layout_version(), layout_version);
+        assert!(board.standings().get("alice").unwrap().time > 1.8);
+    }
+
+    #[test]
+    fn join_answers_only_count_for_the_key_asked_for() {
+        let board = LiveBoard::default();
+
+        board.apply(Message::JoinRejected { key: "alice".to_owned(), reason: JoinError::Taken });
+
+        assert_eq!(board.take_join_answer("bob"), None);
+        assert_eq!(board.take_join_answer("alice"), None);
+
+        board.apply(Message::JoinRejected { key: "alice".to_owned(), reason: JoinError::Taken });
+
+        assert_eq!(board.take_join_answer("alice"), Some(Err(JoinError::Taken)));
+    }
+
+    #[test]
+    fn leaving_removes_the_entry() {
+        let mut client = Client::new(Box::new(MemoryBackend::default()));
+
+        joined(&mut client, "bob", &Board::Standard(Difficulty::Normal)).unwrap();
+        client.register_time(4.0);
+        client.leave();
+        client.register_time(5.0);
+        client.sync();
+
+        assert_eq!(client.get_leaderboard().ranked().count(), 0);
+    }
+
+    #[test]
+    fn runs_finished_offline_are_flushed_after_reconnecting() {
+        let script = Rc::new(RefCell::new(Script::default()));
+        let mut client = Client::new(Box::new(ScriptedBackend(script.clone())));
+
+        script.borrow_mut().status = ConnectionStatus::Offline;
+        client.update();
+
+        assert_eq!(joined(&mut client, "alice", &Board::Standard(Difficulty::Normal)), Ok(()));
+
+        client.register_time(1.0);
+        client.finish_run(4.0, 7);
+        client.leave();
+
+        assert!(script.borrow().joins.is_empty());
+        assert!(script.borrow().submitted.is_empty());
+        assert!(script.borrow().finished.is_empty());
+
+        script.borrow_mut().status = ConnectionStatus::Online;
+        client.update();
+        client.update();
+
+        assert_eq!(script.borrow().joins, vec![("alice".to_owned(), None)]);
+        assert_eq!(script.borrow().finished, vec![("alice".to_owned(), 4.0, 7)]);
+
+        client.leave();
+
+        assert_eq!(script.borrow().left, vec!["alice".to_owned()]);
+    }
+
+    #[test]
+    fn the_current_run_is_joined_again_after_the_connection_drops() {
+        let script = Rc::new(RefCell::new(Script { status: ConnectionStatus::Online, ..Script::default() }));
+        let mut client = Client::new(Box::new(ScriptedBackend(script.clone())));
+
+        joined(&mut client, "bob", &Board::Standard(Difficulty::Normal)).unwrap();
+        client.register_time(1.0);
+
+        script.borrow_mut().status = ConnectionStatus::Reconnecting;
+        client.update();
+        client.register_time(2.0);
+        client.finish_run(3.0, 7);
+
+        script.borrow_mut().status = ConnectionStatus::Online;
+        client.update();
+        client.update();
+        client.update();
+
+        let script = script.borrow();
+
+        assert_eq!(script.joins, vec![("bob".to_owned(), None), ("bob".to_owned(), Some("token for bob".to_owned()))]);
+        assert_eq!(script.submitted, vec![("bob".to_owned(), 1.0)]);
+        assert_eq!(script.finished, vec![("bob".to_owned(), 3.0, 7)]);
+    }
+
+    #[test]
+    fn refused_rejoins_drop_the_entry_and_its_queued_runs() {
+        let script = Rc::new(RefCell::new(Script::default()));
+        let mut client = Client::new(Box::new(ScriptedBackend(script.clone())));
+
+        script.borrow_mut().status = ConnectionStatus::Offline;
+        client.update();
+        joined(&mut client, "carol", &Board::Standard(Difficulty::Normal)).unwrap();
+        client.finish_run(5.0, 7);
+
+        script.borrow_mut().status = ConnectionStatus::Online;
+        script.borrow_mut().taken = true;
+        client.update();
+        client.update();
+        client.update();
+        client.register_time(6.0);
+
+        assert!(client.entry.is_none());
+        assert!(client.queued_scores.is_empty());
+        assert!(script.borrow().submitted.is_empty());
+        assert!(script.borrow().finished.is_empty());
+    }
+
+    #[derive(Default)]
+    struct Script {
+        status: ConnectionStatus,
+        // refuse every join
+        taken: bool,
+        joins: Vec<(String, Option<String>)>,
+        answer: Option<(String, Result<String, JoinError>)>,
+        submitted: Vec<(String, f64)>,
+        finished: Vec<(String, f64, u64)>,
+        left: Vec<String>
+    }
+
+    // answers joins right away while online and records what the client sends
+    struct ScriptedBackend(Rc<RefCell<Script>>);
+
+    impl LeaderboardBackend for ScriptedBackend {
+        fn join(&mut self, _name: &str, key: &str, token: Option<&str>) {
+            let mut script = self.0.borrow_mut();
+            let answer = if script.taken { Err(JoinError::Taken) } else { Ok(format!("token for {key}")) };
+
+            script.joins.push((key.to_owned(), token.map(str::to_owned)));
+            script.answer = Some((key.to_owned(), answer));
+        }
+
+        fn join_answer(&mut self, key: &str) -> Option<Result<String, JoinError>> {
+            self.0.borrow_mut().answer.take_if(|(answered, _)| *answered == key).map(|(_, answer)| answer)
+        }
+
+        fn submit(&mut self, key: &str, time: f64) {
+            self.0.borrow_mut().submitted.push((key.to_owned(), time));
+        }
+
+        fn finish(&mut self, key: &str, time: f64, seed: u64) {
+            self.0.borrow_mut().finished.push((key.to_owned(), time, seed));
+        }
+
+        fn leave(&mut self, key: &str) {
+            self.0.borrow_mut().left.push(key.to_owned());
+        }
+
+        fn snapshot(&self) -> Standings {
+            Standings::default()
+        }
+
+        fn version(&self) -> u64 {
+            0
+        }
+
+        fn status(&self) -> Option<ConnectionStatus> {
+            Some(self.0.borrow().status)
+        }
+    }
+
+    static LEADERBOARD_FOR_TESTS: Lazy<LiveBoard> = Lazy::new(LiveBoard::default);
+
+    // answers joins only when a test applies the server's message to LEADERBOARD_FOR_TESTS
+    struct NetworklessBackend;
+
+    impl LeaderboardBackend for NetworklessBackend {
+        fn join(&mut self, _name: &str, _key: &str, _token: Option<&str>) {}
+
+        fn join_answer(&mut self, key: &str) -> Option<Result<String, JoinError>> {
+            LEADERBOARD_FOR_TESTS.take_join_answer(key)
+        }
+
+        fn submit(&mut self, _key: &str, _time: f64) {
+            panic!("submitted before the join was accepted");
+        }
+
+        fn finish(&mut self, _key: &str, _time: f64, _seed: u64) {
+            panic!("finished before the join was accepted");
+        }
+
+        fn leave(&mut self, _key: &str) {}
+
+        fn snapshot(&self) -> Standings {
+            LEADERBOARD_FOR_TESTS.standings()
+        }
+
+        fn version(&self) -> u64 {
+            LEADERBOARD_FOR_TESTS.version()
+        }
     }
 }
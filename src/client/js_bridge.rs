@@ -0,0 +1,50 @@
+use sapp_jsutils::JsObject;
+
+use crate::client::{ConnectionStatus, JoinError, LeaderboardBackend, Message, Standings, LEADERBOARD};
+
+unsafe extern "C" {
+  fn _leaderboard_send(message: JsObject);
+}
+
+// The socket.io connection owned by plugin.js. Updates come back through `_leaderboard_receive`.
+pub struct JsBridgeBackend;
+
+fn send(message: Message) {
+    unsafe {
+        _leaderboard_send(JsObject::string(&message.encode()));
+    }
+}
+
+impl LeaderboardBackend for JsBridgeBackend {
+    fn join(&mut self, name: &str, key: &str, token: Option<&str>) {
+        send(Message::Join { name: name.to_owned(), key: key.to_owned(), token: token.map(str::to_owned) });
+    }
+
+    fn join_answer(&mut self, key: &str) -> Option<Result<String, JoinError>> {
+        LEADERBOARD.take_join_answer(key)
+    }
+
+    fn submit(&mut self, key: &str, time: f64) {
+        send(Message::Submit { key: key.to_owned(), time });
+    }
+
+    fn finish(&mut self, key: &str, time: f64, seed: u64) {
+        send(Message::Finish { key: key.to_owned(), time, seed: Some(seed) });
+    }
+
+    fn leave(&mut self, key: &str) {
+        send(Message::Leave { key: key.to_owned() });
+    }
+
+    fn snapshot(&self) -> Standings {
+        LEADERBOARD.standings()
+    }
+
+    fn version(&self) -> u64 {
+        LEADERBOARD.version()
+    }
+
+    fn status(&self) -> Option<ConnectionStatus> {
+        Some(LEADERBOARD.status())
+    }
+}
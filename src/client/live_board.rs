@@ -0,0 +1,82 @@
+use std::sync::{Mutex, RwLock};
+
+use macroquad::logging::error;
+use macroquad::miniquad::date;
+
+use crate::calendar::*;
+use crate::client::{board_title_of, ConnectionStatus, JoinError, LeaderboardEntry, Message, Standings};
+
+// The board as the server reports it, the server's answer to the last join and how the connection to it stands.
+// Shared with whatever receives the server's messages: the page on the web, the network thread on native.
+#[derive(Default)]
+pub struct LiveBoard {
+    entries: RwLock<Standings>,
+    join_answer: Mutex<Option<(String, Result<String, JoinError>)>>,
+    status: Mutex<ConnectionStatus>
+}
+
+impl LiveBoard {
+    pub fn standings(&self) -> Standings {
+        self.entries.read().unwrap().clone()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.entries.read().unwrap().version()
+    }
+
+    // the answer for `key` if it arrived, answers for older joins are dropped
+    pub fn take_join_answer(&self, key: &str) -> Option<Result<String, JoinError>> {
+        let mut answer = self.join_answer.lock().unwrap();
+
+        match answer.take() {
+            Some((answered, result)) if answered == key => Some(result),
+            _ => None
+        }
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        *self.status.lock().unwrap()
+    }
+
+    pub fn set_status(&self, status: ConnectionStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+
+    pub fn apply(&self, message: Message) {
+        match message {
+            // from servers that only report times, which are taken as running
+            Message::Submit { key, time } => {
+                let mut entries = self.entries.write().unwrap();
+                let timestamp = date::now();
+                let entry = LeaderboardEntry {
+                    time,
+                    timestamp,
+                    date: utc_date(utc_day(timestamp)),
+                    mode: board_title_of(&key),
+                    live: true,
+                    runs: entries.get(&key).map_or(0, |entry| entry.runs),
+                    seed: None
+                };
+
+                entries.insert(key, entry);
+            }
+            Message::Entry { key, entry } => {
+                self.entries.write()
+                    .unwrap()
+                    .insert(key, entry);
+            }
+            Message::Leave { key } => {
+                self.entries.write()
+                    .unwrap()
+                    .remove(&key);
+            }
+            Message::Snapshot { entries } => self.entries.write().unwrap().replace(entries),
+            Message::JoinAccepted { key, token } => *self.join_answer.lock().unwrap() = Some((key, Ok(token))),
+            Message::JoinRejected { key, reason } => *self.join_answer.lock().unwrap() = Some((key, Err(reason))),
+            Message::Status { status } => self.set_status(status),
+            Message::Error { message } => error!("leaderboard: {message}"),
+            // only ever sent by the game
+            Message::Join { .. } | Message::Finish { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use macroquad::logging::error;
+use serde::{Deserialize, Serialize};
+
+use crate::client::{JoinError, LeaderboardBackend, RunRecord, Standings};
+use crate::storage;
+
+const LEADERBOARD_FILE: &str = "leaderboard.toml";
+
+#[derive(Default, Serialize, Deserialize)]
+struct SavedBoard {
+    #[serde(default)]
+    records: BTreeMap<String, RunRecord>
+}
+
+// A board for a single machine: the best run of every key is saved when a run finishes,
+// runs in progress are shown next to them.
+pub struct LocalFileBackend {
+    saved: SavedBoard,
+    standings: Standings,
+    join_answer: Option<(String, Result<String, JoinError>)>
+}
+
+impl LocalFileBackend {
+    // a missing or unreadable file starts an empty board
+    pub fn load() -> LocalFileBackend {
+        let saved = match storage::read(LEADERBOARD_FILE) {
+            Some(bytes) => String::from_utf8(bytes)
+                .map_err(|error| error.to_string())
+                .and_then(|text| toml::from_str(&text).map_err(|error| error.to_string()))
+                .unwrap_or_else(|error| {
+                    error!("failed to read {LEADERBOARD_FILE}: {error}");
+                    SavedBoard::default()
+                }),
+            None => SavedBoard::default()
+        };
+
+        let mut standings = Standings::default();
+        standings.replace(saved.records.iter()
+            .filter_map(|(key, record)| Some((key.clone(), record.entry(key)?)))
+            .collect());
+
+        LocalFileBackend {
+            saved,
+            standings,
+            join_answer: None
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let text = toml::to_string(&self.saved).map_err(|error| error.to_string())?;
+
+        storage::write(LEADERBOARD_FILE, text.as_bytes())
+    }
+
+    fn update(&mut self, key: &str, change: impl FnOnce(&mut RunRecord)) {
+        let record = self.saved.records.entry(key.to_owned()).or_default();
+        change(record);
+
+        match record.entry(key) {
+            Some(entry) => self.standings.insert(key.to_owned(), entry),
+            None => {
+                self.saved.records.remove(key);
+                self.standings.remove(key);
+            }
+        }
+    }
+}
+
+impl LeaderboardBackend for LocalFileBackend {
+    // only runs in progress hold a key, saved runs belong to whoever plays on this machine
+    fn join(&mut self, _name: &str, key: &str, _token: Option<&str>) {
+        let live = self.saved.records.get(key).is_some_and(RunRecord::is_live);
+        let answer = if live { Err(JoinError::Taken) } else { Ok(String::new()) };
+
+        self.join_answer = Some((key.to_owned(), answer));
+    }
+
+    fn join_answer(&mut self, key: &str) -> Option<Result<String, JoinError>> {
+        self.join_answer.take_if(|(answered, _)| *answered == key).map(|(_, answer)| answer)
+    }
+
+    fn submit(&mut self, key: &str, time: f64) {
+        self.update(key, |record| record.submit(time));
+    }
+
+    // saved every time, the run count changes even when the best doesn't
+    fn finish(&mut self, key: &str, time: f64, seed: u64) {
+        self.update(key, |record| record.finish(time, seed));
+
+        if let Err(error) = self.save() {
+            error!("failed to save the leaderboard: {error}");
+        }
+    }
+
+    fn leave(&mut self, key: &str) {
+        if self.saved.records.contains_key(key) {
+            self.update(key, RunRecord::leave);
+        }
+    }
+
+    fn snapshot(&self) -> Standings {
+        self.standings.clone()
+    }
+
+    fn version(&self) -> u64 {
+        self.standings.version()
+    }
+}
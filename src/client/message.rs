@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::client::{seed_text, ConnectionStatus, JoinError, LeaderboardEntry};
+
+// Bumped whenever a message changes shape. plugin.js stamps the same number on everything it sends.
+pub const PROTOCOL_VERSION: u64 = 4;
+
+// Everything that crosses the JS bridge, as JSON objects like {"version":4,"type":"submit","key":"bob","time":2.5}.
+// The game sends Join, Submit (the running time), Finish (the final time of a run) and Leave. The page answers joins
+// with the server's JoinAccepted or JoinRejected and forwards the server's board as Entry (a key changed),
+// Leave (a key is gone) and Snapshot (the whole board). Servers that only report times send Submit instead of Entry.
+// Status reports the socket connecting, dropping and coming back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Message {
+    Join {
+        name: String,
+        key: String,
+        // from an earlier JoinAccepted for the same key
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>
+    },
+    JoinAccepted { key: String, token: String },
+    JoinRejected { key: String, reason: JoinError },
+    Submit { key: String, time: f64 },
+    Finish {
+        key: String,
+        time: f64,
+        #[serde(with = "seed_text")]
+        seed: Option<u64>
+    },
+    Entry { key: String, entry: LeaderboardEntry },
+    Leave { key: String },
+    Snapshot { entries: BTreeMap<String, LeaderboardEntry> },
+    Status { status: ConnectionStatus },
+    Error { message: String }
+}
+
+impl Message {
+    // only the web build sends messages as text, native backends hand them over directly
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn encode(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("messages always serialize");
+        value["version"] = Value::from(PROTOCOL_VERSION);
+
+        value.to_string()
+    }
+
+    pub fn decode(text: &str) -> Result<Message, String> {
+        let mut value: Value = serde_json::from_str(text).map_err(|error| format!("malformed message: {error}"))?;
+
+        let Some(object) = value.as_object_mut() else {
+            return Err("message is not an object".to_owned());
+        };
+
+        match object.remove("version").as_ref().map(Value::as_u64) {
+            Some(Some(PROTOCOL_VERSION)) => {}
+            Some(Some(version)) => return Err(format!("unsupported protocol version {version}, expected {PROTOCOL_VERSION}")),
+            Some(None) => return Err("message version is not a number".to_owned()),
+            None => return Err("message has no version".to_owned())
+        }
+
+        serde_json::from_value(value).map_err(|error| format!("invalid message: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip(message: Message, expected: Value) {
+        let encoded: Value = serde_json::from_str(&message.encode()).unwrap();
+
+        assert_eq!(encoded, expected);
+        assert_eq!(Message::decode(&expected.to_string()).unwrap(), message);
+    }
+
+    #[test]
+    fn join() {
+        round_trip(
+            Message::Join { name: "alice".to_owned(), key: "alice [Hard]".to_owned(), token: None },
+            json!({ "version": 4, "type": "join", "name": "alice", "key": "alice [Hard]" }));
+
+        round_trip(
+            Message::Join { name: "alice".to_owned(), key: "alice".to_owned(), token: Some("3f2a".to_owned()) },
+            json!({ "version": 4, "type": "join", "name": "alice", "key": "alice", "token": "3f2a" }));
+    }
+
+    #[test]
+    fn join_accepted() {
+        round_trip(
+            Message::JoinAccepted { key: "alice".to_owned(), token: "3f2a".to_owned() },
+            json!({ "version": 4, "type": "join_accepted", "key": "alice", "token": "3f2a" }));
+    }
+
+    #[test]
+    fn join_rejected() {
+        round_trip(
+            Message::JoinRejected { key: "alice".to_owned(), reason: JoinError::Taken },
+            json!({ "version": 4, "type": "join_rejected", "key": "alice", "reason": "taken" }));
+
+        round_trip(
+            Message::JoinRejected { key: "al[ice".to_owned(), reason: JoinError::InvalidCharacters },
+            json!({ "version": 4, "type": "join_rejected", "key": "al[ice", "reason": "invalid_characters" }));
+
+        assert!(Message::decode(r#"{"version":4,"type":"join_rejected","key":"alice","reason":"banned"}"#).is_err());
+    }
+
+    #[test]
+    fn submit() {
+        round_trip(
+            Message::Submit { key: "bob".to_owned(), time: 2.5 },
+            json!({ "version": 4, "type": "submit", "key": "bob", "time": 2.5 }));
+    }
+
+    #[test]
+    fn leave() {
+        round_trip(
+            Message::Leave { key: "bob".to_owned() },
+            json!({ "version": 4, "type": "leave", "key": "bob" }));
+    }
+
+    #[test]
+    fn finish() {
+        round_trip(
+            Message::Finish { key: "bob".to_owned(), time: 12.5, seed: Some(u64::MAX) },
+            json!({ "version": 4, "type": "finish", "key": "bob", "time": 12.5, "seed": "18446744073709551615" }));
+    }
+
+    fn entry() -> (LeaderboardEntry, Value) {
+        let entry = LeaderboardEntry {
+            time: 2.5,
+            timestamp: 1792300000.5,
+            date: "2026-10-18".to_owned(),
+            mode: "Hard".to_owned(),
+            live: true,
+            runs: 3,
+            seed: Some(42)
+        };
+        let json = json!({ "time": 2.5, "timestamp": 1792300000.5, "date": "2026-10-18", "mode": "Hard", "live": true, "runs": 3, "seed": "42" });
+
+        (entry, json)
+    }
+
+    #[test]
+    fn entry_update() {
+        let (entry, json) = entry();
+
+        round_trip(
+            Message::Entry { key: "bob [Hard]".to_owned(), entry },
+            json!({ "version": 4, "type": "entry", "key": "bob [Hard]", "entry": json }));
+    }
+
+    #[test]
+    fn snapshot() {
+        let (entry, json) = entry();
+
+        round_trip(
+            Message::Snapshot { entries: BTreeMap::from([("carol [Hard]".to_owned(), entry)]) },
+            json!({ "version": 4, "type": "snapshot", "entries": { "carol [Hard]": json } }));
+    }
+
+    #[test]
+    fn entries_without_a_seed() {
+        let (mut entry, mut json) = entry();
+        entry.seed = None;
+        json.as_object_mut().unwrap().remove("seed");
+
+        round_trip(
+            Message::Entry { key: "bob".to_owned(), entry },
+            json!({ "version": 4, "type": "entry", "key": "bob", "entry": json }));
+    }
+
+    #[test]
+    fn status() {
+        round_trip(
+            Message::Status { status: ConnectionStatus::Reconnecting },
+            json!({ "version": 4, "type": "status", "status": "reconnecting" }));
+
+        assert!(Message::decode(r#"{"version":4,"type":"status","status":"asleep"}"#).is_err());
+    }
+
+    #[test]
+    fn error() {
+        round_trip(
+            Message::Error { message: "connection lost".to_owned() },
+            json!({ "version": 4, "type": "error", "message": "connection lost" }));
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert!(Message::decode("{\"version\":1,").unwrap_err().starts_with("malformed message"));
+        assert_eq!(Message::decode("[1, 2]").unwrap_err(), "message is not an object");
+    }
+
+    #[test]
+    fn rejects_missing_or_unknown_versions() {
+        assert_eq!(Message::decode(r#"{"type":"leave","key":"bob"}"#).unwrap_err(), "message has no version");
+        assert_eq!(Message::decode(r#"{"version":"1","type":"leave","key":"bob"}"#).unwrap_err(), "message version is not a number");
+        assert_eq!(
+            Message::decode(r#"{"version":3,"type":"leave","key":"bob"}"#).unwrap_err(),
+            "unsupported protocol version 3, expected 4");
+    }
+
+    #[test]
+    fn rejects_unknown_types_and_bad_fields() {
+        assert!(Message::decode(r#"{"version":4,"type":"teleport"}"#).unwrap_err().starts_with("invalid message"));
+        assert!(Message::decode(r#"{"version":4,"type":"submit","key":"bob"}"#).unwrap_err().starts_with("invalid message"));
+        assert!(Message::decode(r#"{"version":4,"type":"submit","key":"bob","time":"fast"}"#).unwrap_err().starts_with("invalid message"));
+        assert!(Message::decode(r#"{"version":4,"type":"leave","key":"bob","extra":true}"#).unwrap_err().starts_with("invalid message"));
+    }
+}
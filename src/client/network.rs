@@ -0,0 +1,495 @@
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use macroquad::logging::error;
+use serde_json::{Value, json};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::client::{self, ConnectionStatus, JoinError, LeaderboardBackend, LiveBoard, Standings};
+
+// how often the network thread looks for outgoing packets while waiting on the socket
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// the wait before reconnecting doubles after every failed attempt, up to MAX_RETRY_DELAY
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+const SOCKET_IO_PATH: &str = "/socket.io/?EIO=4&transport=websocket";
+
+// Talks socket.io over a websocket, plain or TLS, to a leaderboard server such as speedy_jumper_server, the same
+// protocol plugin.js uses. The connection lives on its own thread; updates land in `board` the way
+// `_leaderboard_receive` fills it on the web. A lost connection is retried until the backend is dropped.
+pub struct NetworkBackend {
+    board: &'static LiveBoard,
+    outgoing: Sender<String>
+}
+
+impl NetworkBackend {
+    // `endpoint` is a ws://, wss://, http:// or https:// address, the socket.io path is added when it has none
+    pub fn connect(endpoint: &str, board: &'static LiveBoard) -> Result<NetworkBackend, String> {
+        let url = socket_io_url(endpoint)?;
+        let (outgoing, receiver) = channel();
+
+        thread::Builder::new()
+            .name("leaderboard".to_owned())
+            .spawn(move || keep_connected(&url, board, receiver))
+            .map_err(|error| error.to_string())?;
+
+        Ok(NetworkBackend { board, outgoing })
+    }
+
+    // the thread only stops sending once the backend is gone, so a failed send has nowhere to report to
+    fn emit(&self, name: &str, argument: Value) {
+        let _ = self.outgoing.send(format!("42{}", json!([name, argument])));
+    }
+}
+
+impl LeaderboardBackend for NetworkBackend {
+    fn join(&mut self, name: &str, key: &str, token: Option<&str>) {
+        self.emit("join", json!({ "name": name, "key": key, "token": token }));
+    }
+
+    fn join_answer(&mut self, key: &str) -> Option<Result<String, JoinError>> {
+        self.board.take_join_answer(key)
+    }
+
+    fn submit(&mut self, key: &str, time: f64) {
+        self.emit("player_time", json!([key, time]));
+    }
+
+    fn finish(&mut self, key: &str, time: f64, seed: u64) {
+        self.emit("run_finished", json!({ "key": key, "time": time, "seed": seed.to_string() }));
+    }
+
+    // the server drops entries with a negative time
+    fn leave(&mut self, key: &str) {
+        self.submit(key, -1.0);
+    }
+
+    fn snapshot(&self) -> Standings {
+        self.board.standings()
+    }
+
+    fn version(&self) -> u64 {
+        self.board.version()
+    }
+
+    fn status(&self) -> Option<ConnectionStatus> {
+        Some(self.board.status())
+    }
+}
+
+fn socket_io_url(endpoint: &str) -> Result<String, String> {
+    let url = if let Some(rest) = endpoint.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else if let Some(rest) = endpoint.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+        endpoint.to_owned()
+    } else {
+        return Err("only ws://, wss://, http:// and https:// servers are supported".to_owned());
+    };
+
+    match url.splitn(4, '/').nth(3) {
+        None | Some("") => Ok(format!("{}{SOCKET_IO_PATH}", url.trim_end_matches('/'))),
+        Some(_) => Ok(url)
+    }
+}
+
+fn keep_connected(url: &str, board: &LiveBoard, outgoing: Receiver<String>) {
+    let mut retry_delay = FIRST_RETRY_DELAY;
+
+    loop {
+        match run_connection(url, board, &outgoing, &mut retry_delay) {
+            Ok(()) => return,
+            Err(error) => error!("leaderboard connection to {url} failed: {error}")
+        }
+
+        board.set_status(ConnectionStatus::Offline);
+
+        if !wait_to_retry(&outgoing, retry_delay) {
+            return;
+        }
+
+        retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        board.set_status(ConnectionStatus::Reconnecting);
+    }
+}
+
+// Packets sent while offline are dropped, the client holds back what it still needs until it's online again.
+// false once the backend is gone.
+fn wait_to_retry(outgoing: &Receiver<String>, delay: Duration) -> bool {
+    let deadline = Instant::now() + delay;
+
+    loop {
+        match outgoing.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) => return true,
+            Err(RecvTimeoutError::Disconnected) => return false
+        }
+    }
+}
+
+// Ok once the backend was dropped, `retry_delay` is reset as soon as the server answers.
+fn run_connection(url: &str, board: &LiveBoard, outgoing: &Receiver<String>, retry_delay: &mut Duration) -> Result<(), String> {
+    let request = url.into_client_request().map_err(|error| error.to_string())?;
+    let host = request.uri().host().ok_or("the server address has no host")?.to_owned();
+    let secure = request.uri().scheme_str() == Some("wss");
+    let port = request.uri().port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+    let stream = TcpStream::connect((host.as_str(), port)).map_err(|error| error.to_string())?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|error| error.to_string())?;
+
+    // the same socket, to change the timeout once TLS wraps the stream
+    let raw_stream = stream.try_clone().map_err(|error| error.to_string())?;
+
+    let (mut socket, _) = tungstenite::client_tls(request, stream).map_err(|error| error.to_string())?;
+
+    let open = read_text(&mut socket)?;
+
+    if !open.starts_with('0') {
+        return Err(format!("expected an engine.io open packet, got {open}"));
+    }
+
+    send(&mut socket, "40")?;
+
+    // not online until the server acknowledged the connect
+    loop {
+        let packet = read_text(&mut socket)?;
+
+        if packet.starts_with("40") {
+            break;
+        }
+
+        if let Some(reason) = packet.strip_prefix("44") {
+            return Err(format!("the server refused the connect: {reason}"));
+        }
+
+        if packet == "2" {
+            send(&mut socket, "3")?;
+        }
+    }
+
+    // the server sends its whole board after the connect
+    board.apply(client::Message::Snapshot { entries: BTreeMap::new() });
+    board.set_status(ConnectionStatus::Online);
+    *retry_delay = FIRST_RETRY_DELAY;
+
+    raw_stream.set_read_timeout(Some(POLL_INTERVAL)).map_err(|error| error.to_string())?;
+
+    // servers that send whole entries repeat them as update_player for older clients
+    let mut sends_entries = false;
+
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => handle_packet(&mut socket, text.as_str(), board, &mut sends_entries)?,
+            Ok(Message::Close(_)) => return Err("the server closed the connection".to_owned()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(error) => return Err(error.to_string())
+        }
+
+        loop {
+            match outgoing.try_recv() {
+                Ok(packet) => send(&mut socket, &packet)?,
+                Err(TryRecvError::Empty) => break,
+                // the game dropped the backend after sending its last packets
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn handle_packet(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>, text: &str, board: &LiveBoard, sends_entries: &mut bool) -> Result<(), String> {
+    // engine.io ping
+    if text == "2" {
+        return send(socket, "3");
+    }
+
+    let Some(event) = text.strip_prefix("42") else {
+        return Ok(());
+    };
+
+    let Ok(Value::Array(event)) = serde_json::from_str::<Value>(event) else {
+        return Err(format!("malformed event {event}"));
+    };
+
+    let [Value::String(name), argument] = event.as_slice() else {
+        return Ok(());
+    };
+
+    let message = match name.as_str() {
+        // a negative score means the player left
+        "update_player" => match argument.as_array().map(Vec::as_slice) {
+            Some([Value::String(key), Value::Number(score)]) => match score.as_f64() {
+                Some(time) if time < 0.0 => client::Message::Leave { key: key.clone() },
+                Some(_) if *sends_entries => return Ok(()),
+                Some(time) => client::Message::Submit { key: key.clone(), time },
+                None => return Ok(())
+            },
+            _ => return Err(format!("malformed update_player {argument}"))
+        },
+        "entry" | "join_accepted" | "join_rejected" => {
+            *sends_entries |= name == "entry";
+
+            let mut message = argument.clone();
+            message["type"] = Value::from(name.as_str());
+
+            serde_json::from_value(message).map_err(|error| format!("malformed {name}: {error}"))?
+        }
+        _ => return Ok(())
+    };
+
+    board.apply(message);
+
+    Ok(())
+}
+
+fn read_text(socket: &mut WebSocket<impl Read + Write>) -> Result<String, String> {
+    loop {
+        match socket.read().map_err(|error| error.to_string())? {
+            Message::Text(text) => return Ok(text.as_str().to_owned()),
+            Message::Close(_) => return Err("the server closed the connection".to_owned()),
+            _ => {}
+        }
+    }
+}
+
+fn send(socket: &mut WebSocket<impl Read + Write>, packet: &str) -> Result<(), String> {
+    socket.send(Message::text(packet)).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    use super::*;
+
+    fn greet(listener: &TcpListener) -> WebSocket<TcpStream> {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+
+        send(&mut socket, r#"0{"sid":"stub","upgrades":[],"pingInterval":25000,"pingTimeout":20000}"#).unwrap();
+        assert_eq!(read_text(&mut socket).unwrap(), "40");
+        send(&mut socket, r#"40{"sid":"stub"}"#).unwrap();
+
+        socket
+    }
+
+    // accepts one player, greets it like a socket.io server and reports every event it sends
+    fn stub_server(updates: Vec<&'static str>) -> (String, Receiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (events, received) = channel();
+
+        thread::spawn(move || {
+            let mut socket = greet(&listener);
+
+            for update in updates {
+                send(&mut socket, update).unwrap();
+            }
+
+            while let Ok(text) = read_text(&mut socket) {
+                if let Some(event) = text.strip_prefix("42") && events.send(serde_json::from_str(event).unwrap()).is_err() {
+                    return;
+                }
+            }
+        });
+
+        (format!("ws://{address}"), received)
+    }
+
+    fn test_board() -> &'static LiveBoard {
+        Box::leak(Box::new(LiveBoard::default()))
+    }
+
+    fn wait_for(mut condition: impl FnMut() -> bool) {
+        let start = Instant::now();
+
+        while !condition() {
+            assert!(start.elapsed() < Duration::from_secs(3), "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn sends_the_same_events_as_the_web_build() {
+        let (endpoint, events) = stub_server(Vec::new());
+        let mut backend = NetworkBackend::connect(&endpoint, test_board()).unwrap();
+
+        backend.join("alice", "alice [Hard]", Some("3f2a"));
+        backend.submit("alice [Hard]", 2.5);
+        backend.finish("alice [Hard]", 3.0, u64::MAX);
+        backend.leave("alice [Hard]");
+
+        let timeout = Duration::from_secs(3);
+
+        assert_eq!(events.recv_timeout(timeout).unwrap(), json!(["join", { "name": "alice", "key": "alice [Hard]", "token": "3f2a" }]));
+        assert_eq!(events.recv_timeout(timeout).unwrap(), json!(["player_time", ["alice [Hard]", 2.5]]));
+        assert_eq!(events.recv_timeout(timeout).unwrap(), json!(["run_finished", { "key": "alice [Hard]", "time": 3.0, "seed": "18446744073709551615" }]));
+        assert_eq!(events.recv_timeout(timeout).unwrap(), json!(["player_time", ["alice [Hard]", -1.0]]));
+    }
+
+    #[test]
+    fn updates_from_the_server_fill_the_board() {
+        let (endpoint, _events) = stub_server(vec![
+            r#"42["update_player",["bob",3.5]]"#,
+            r#"42["update_player",["carol",1.0]]"#,
+            r#"42["update_player",["bob",-1]]"#,
+            r#"42["update_player",["dave",8.0]]"#
+        ]);
+        let board = test_board();
+        let backend = NetworkBackend::connect(&endpoint, board).unwrap();
+
+        wait_for(|| backend.snapshot().get("dave").is_some());
+
+        let ranked: Vec<_> = backend.snapshot().ranked().map(|(key, entry)| (key.to_owned(), entry.time)).collect();
+
+        assert_eq!(ranked, vec![("dave".to_owned(), 8.0), ("carol".to_owned(), 1.0)]);
+    }
+
+    #[test]
+    fn whole_entries_replace_bare_times() {
+        let (endpoint, _events) = stub_server(vec![
+            r#"42["update_player",["bob",3.5]]"#,
+            r#"42["update_player",["carol",4.0]]"#,
+            r#"42["entry",{"key":"carol","entry":{"time":4.0,"timestamp":100.0,"date":"2026-10-18","mode":"Normal","live":false,"runs":2,"seed":"7"}}]"#,
+            r#"42["update_player",["carol",9.0]]"#,
+            r#"42["update_player",["bob",-1]]"#,
+            r#"42["update_player",["dave",1.0]]"#,
+            r#"42["entry",{"key":"erin","entry":{"time":1.0,"timestamp":100.0,"date":"2026-10-18","mode":"Normal","live":true,"runs":0}}]"#
+        ]);
+        let backend = NetworkBackend::connect(&endpoint, test_board()).unwrap();
+
+        wait_for(|| backend.snapshot().get("erin").is_some());
+
+        let snapshot = backend.snapshot();
+        let carol = snapshot.get("carol").unwrap();
+
+        assert_eq!((carol.time, carol.live, carol.runs, carol.seed), (4.0, false, 2, Some(7)));
+        assert!(snapshot.get("bob").is_none());
+        // repeated for older clients, which only read update_player
+        assert!(snapshot.get("dave").is_none());
+    }
+
+    #[test]
+    fn join_answers_reach_the_backend() {
+        let (endpoint, _events) = stub_server(vec![
+            r#"42["join_rejected",{"key":"alice","reason":"taken"}]"#
+        ]);
+        let backend = NetworkBackend::connect(&endpoint, test_board()).unwrap();
+
+        wait_for(|| backend.board.take_join_answer("alice").is_some_and(|answer| answer == Err(JoinError::Taken)));
+
+        let (endpoint, _events) = stub_server(vec![
+            r#"42["join_accepted",{"key":"bob","token":"3f2a"}]"#
+        ]);
+        let mut backend = NetworkBackend::connect(&endpoint, test_board()).unwrap();
+        let mut answer = None;
+
+        wait_for(|| {
+            answer = backend.join_answer("bob");
+            answer.is_some()
+        });
+
+        assert_eq!(answer, Some(Ok("3f2a".to_owned())));
+    }
+
+    #[test]
+    fn reconnects_after_losing_the_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        let board = test_board();
+
+        assert_eq!(board.status(), ConnectionStatus::Connecting);
+
+        let (dropped, wait_for_drop) = channel();
+
+        thread::spawn(move || {
+            let mut socket = greet(&listener);
+            send(&mut socket, r#"42["update_player",["bob",3.5]]"#).unwrap();
+
+            wait_for_drop.recv().unwrap();
+            drop(socket);
+
+            let mut socket = greet(&listener);
+            send(&mut socket, r#"42["update_player",["carol",1.0]]"#).unwrap();
+
+            while read_text(&mut socket).is_ok() {}
+        });
+
+        let backend = NetworkBackend::connect(&endpoint, board).unwrap();
+
+        wait_for(|| backend.snapshot().get("bob").is_some());
+        assert_eq!(backend.status(), Some(ConnectionStatus::Online));
+
+        dropped.send(()).unwrap();
+
+        wait_for(|| backend.status() != Some(ConnectionStatus::Online));
+        wait_for(|| backend.snapshot().get("carol").is_some());
+
+        // the old board went with the old connection
+        assert!(backend.snapshot().get("bob").is_none());
+        assert_eq!(backend.status(), Some(ConnectionStatus::Online));
+    }
+
+    #[test]
+    fn stays_connecting_until_the_server_acknowledges_the_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        let (acknowledge, wait_to_acknowledge) = channel();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+
+            send(&mut socket, r#"0{"sid":"stub","upgrades":[],"pingInterval":25000,"pingTimeout":20000}"#).unwrap();
+            assert_eq!(read_text(&mut socket).unwrap(), "40");
+
+            wait_to_acknowledge.recv().unwrap();
+            send(&mut socket, r#"40{"sid":"stub"}"#).unwrap();
+
+            while read_text(&mut socket).is_ok() {}
+        });
+
+        let backend = NetworkBackend::connect(&endpoint, test_board()).unwrap();
+
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(backend.status(), Some(ConnectionStatus::Connecting));
+
+        acknowledge.send(()).unwrap();
+
+        wait_for(|| backend.status() == Some(ConnectionStatus::Online));
+    }
+
+    #[test]
+    fn unreachable_servers_are_reported_offline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("ws://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let backend = NetworkBackend::connect(&endpoint, test_board()).unwrap();
+
+        wait_for(|| backend.status() == Some(ConnectionStatus::Offline));
+    }
+
+    #[test]
+    fn endpoints_get_the_socket_io_path() {
+        assert_eq!(socket_io_url("http://localhost:3000").unwrap(), "ws://localhost:3000/socket.io/?EIO=4&transport=websocket");
+        assert_eq!(socket_io_url("ws://localhost:3000/").unwrap(), "ws://localhost:3000/socket.io/?EIO=4&transport=websocket");
+        assert_eq!(socket_io_url("ws://example.com/custom").unwrap(), "ws://example.com/custom");
+        assert_eq!(socket_io_url("https://example.com").unwrap(), "wss://example.com/socket.io/?EIO=4&transport=websocket");
+        assert_eq!(socket_io_url("wss://example.com/custom").unwrap(), "wss://example.com/custom");
+        assert!(socket_io_url("ftp://example.com").is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use macroquad::miniquad::date;
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::*;
+use crate::client::{board_title_of, seed_text, LeaderboardEntry};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinishedRun {
+    pub time: f64,
+    pub timestamp: f64,
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "seed_text")]
+    pub seed: Option<u64>
+}
+
+// What a backend without a server keeps of one key: its best finished run, how many runs finished
+// and the time of the run in progress.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    #[serde(default)]
+    pub best: Option<FinishedRun>,
+    #[serde(default)]
+    pub runs: u32,
+    // (time, timestamp), nothing is running after a restart
+    #[serde(skip)]
+    pub live: Option<(f64, f64)>
+}
+
+impl RunRecord {
+    pub fn is_live(&self) -> bool {
+        self.live.is_some()
+    }
+
+    pub fn submit(&mut self, time: f64) {
+        self.live = Some((time, date::now()));
+    }
+
+    pub fn finish(&mut self, time: f64, seed: u64) {
+        self.live = None;
+        self.runs += 1;
+
+        if self.best.as_ref().is_some_and(|best| best.time >= time) {
+            return;
+        }
+
+        let timestamp = date::now();
+
+        self.best = Some(FinishedRun { time, timestamp, date: utc_date(utc_day(timestamp)), seed: Some(seed) });
+    }
+
+    pub fn leave(&mut self) {
+        self.live = None;
+    }
+
+    // None while there's nothing to show
+    pub fn entry(&self, key: &str) -> Option<LeaderboardEntry> {
+        let (time, timestamp, date, seed) = match (&self.best, self.live) {
+            (Some(best), Some((time, _))) if best.time >= time => (best.time, best.timestamp, best.date.clone(), best.seed),
+            (_, Some((time, timestamp))) => (time, timestamp, utc_date(utc_day(timestamp)), None),
+            (Some(best), None) => (best.time, best.timestamp, best.date.clone(), best.seed),
+            (None, None) => return None
+        };
+
+        Some(LeaderboardEntry {
+            time,
+            timestamp,
+            date,
+            mode: board_title_of(key),
+            live: self.is_live(),
+            runs: self.runs,
+            seed
+        })
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+
+// One key's standing on its board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeaderboardEntry {
+    // the best finished time, or the running time of a live run that beats it
+    pub time: f64,
+    // when `time` was reached, in seconds since the unix epoch; the earlier of two equal times ranks higher
+    pub timestamp: f64,
+    // UTC date of `time`, YYYY-MM-DD
+    pub date: String,
+    // title of the board, e.g. "Hard" or "Daily 2026-10-18"
+    pub mode: String,
+    // a run is in progress
+    pub live: bool,
+    // finished runs
+    pub runs: u32,
+    // of the run behind `time`, unknown for running times and servers that don't keep it
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "seed_text")]
+    pub seed: Option<u64>
+}
+
+// Seeds use all 64 bits, more than a JavaScript number or a TOML integer holds, so they're written as text.
+pub mod seed_text {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(seed: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match seed {
+            Some(seed) => serializer.serialize_str(&seed.to_string()),
+            None => serializer.serialize_none()
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| text.parse().map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+// A board's entries together with their ranking, kept in order as entries change so nothing has to be
+// sorted when the board is drawn. `version` changes with every change, readers only copy the board when it did.
+// `layout_version` only changes when entries come, go or change places, not when a time changes in place.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Standings {
+    entries: BTreeMap<String, LeaderboardEntry>,
+    // (time, timestamp, key), best first
+    ranked: Vec<(f64, f64, String)>,
+    version: u64,
+    layout_version: u64
+}
+
+fn rank_order(a: &(f64, f64, String), b: &(f64, f64, String)) -> Ordering {
+    b.0.total_cmp(&a.0)
+        .then_with(|| a.1.total_cmp(&b.1))
+        .then_with(|| a.2.cmp(&b.2))
+}
+
+fn rank_key(key: &str, entry: &LeaderboardEntry) -> (f64, f64, String) {
+    (entry.time, entry.timestamp, key.to_owned())
+}
+
+impl Standings {
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn layout_version(&self) -> u64 {
+        self.layout_version
+    }
+
+    pub fn get(&self, key: &str) -> Option<&LeaderboardEntry> {
+        self.entries.get(key)
+    }
+
+    // best first
+    pub fn ranked(&self) -> impl Iterator<Item = (&str, &LeaderboardEntry)> {
+        self.ranked.iter().map(|(_, _, key)| (key.as_str(), &self.entries[key]))
+    }
+
+    // entries whose time isn't a number can't be ranked and are dropped
+    pub fn insert(&mut self, key: String, entry: LeaderboardEntry) {
+        if entry.time.is_nan() || entry.timestamp.is_nan() {
+            return;
+        }
+
+        if self.entries.get(&key) == Some(&entry) {
+            return;
+        }
+
+        let previous_index = self.unrank(&key);
+
+        let rank = rank_key(&key, &entry);
+        let index = self.ranked.binary_search_by(|other| rank_order(other, &rank)).unwrap_or_else(|index| index);
+
+        self.ranked.insert(index, rank);
+        self.entries.insert(key, entry);
+        self.version += 1;
+
+        // a live time ticking up without passing anyone keeps its row
+        if previous_index != Some(index) {
+            self.layout_version += 1;
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        if self.unrank(key).is_some() {
+            self.entries.remove(key);
+            self.version += 1;
+            self.layout_version += 1;
+        }
+    }
+
+    pub fn replace(&mut self, mut entries: BTreeMap<String, LeaderboardEntry>) {
+        entries.retain(|_, entry| !entry.time.is_nan() && !entry.timestamp.is_nan());
+
+        self.ranked = entries.iter().map(|(key, entry)| rank_key(key, entry)).collect();
+        self.ranked.sort_by(rank_order);
+
+        self.entries = entries;
+        self.version += 1;
+        self.layout_version += 1;
+    }
+
+    // where the key was ranked, None if it wasn't on the board
+    fn unrank(&mut self, key: &str) -> Option<usize> {
+        let entry = self.entries.get(key)?;
+        let rank = rank_key(key, entry);
+        let index = self.ranked.binary_search_by(|other| rank_order(other, &rank)).ok()?;
+
+        self.ranked.remove(index);
+
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(time: f64, timestamp: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            time,
+            timestamp,
+            date: "2026-10-18".to_owned(),
+            mode: "Normal".to_owned(),
+            live: false,
+            runs: 1,
+            seed: None
+        }
+    }
+
+    fn ranked(standings: &Standings) -> Vec<(&str, f64)> {
+        standings.ranked().map(|(key, entry)| (key, entry.time)).collect()
+    }
+
+    #[test]
+    fn entries_stay_ranked_as_they_change() {
+        let mut standings = Standings::default();
+
+        standings.insert("bob".to_owned(), entry(3.0, 1.0));
+        standings.insert("carol".to_owned(), entry(5.0, 2.0));
+        standings.insert("alice".to_owned(), entry(4.0, 3.0));
+        standings.insert("bob".to_owned(), entry(7.0, 4.0));
+        standings.remove("carol");
+
+        assert_eq!(ranked(&standings), vec![("bob", 7.0), ("alice", 4.0)]);
+        assert!(standings.get("carol").is_none());
+    }
+
+    #[test]
+    fn ties_go_to_whoever_got_there_first() {
+        let mut standings = Standings::default();
+
+        standings.insert("alice".to_owned(), entry(3.0, 20.0));
+        standings.insert("bob".to_owned(), entry(3.0, 10.0));
+        standings.insert("carol".to_owned(), entry(3.0, 30.0));
+
+        assert_eq!(ranked(&standings), vec![("bob", 3.0), ("alice", 3.0), ("carol", 3.0)]);
+    }
+
+    #[test]
+    fn only_changes_bump_the_version() {
+        let mut standings = Standings::default();
+
+        standings.insert("bob".to_owned(), entry(3.0, 1.0));
+        let version = standings.version();
+
+        standings.insert("bob".to_owned(), entry(3.0, 1.0));
+        standings.remove("carol");
+        standings.insert("nan".to_owned(), entry(f64::NAN, 1.0));
+
+        assert_eq!(standings.version(), version);
+
+        standings.remove("bob");
+
+        assert!(standings.version() > version);
+        assert!(ranked(&standings).is_empty());
+    }
+
+    #[test]
+    fn only_moving_rows_bump_the_layout_version() {
+        let mut standings = Standings::default();
+
+        standings.insert("bob".to_owned(), entry(5.0, 1.0));
+        standings.insert("alice".to_owned(), entry(1.0, 2.0));
+        let (version, layout_version) = (standings.version(), standings.layout_version());
+
+        standings.insert("alice".to_owned(), entry(2.0, 3.0));
+        standings.insert("alice".to_owned(), entry(3.0, 4.0));
+
+        assert!(standings.version() > version);
+        assert_eq!(standings.layout_version(), layout_version);
+
+        standings.insert("alice".to_owned(), entry(6.0, 5.0));
+
+        assert!(standings.layout_version() > layout_version);
+        assert_eq!(ranked(&standings), vec![("alice", 6.0), ("bob", 5.0)]);
+    }
+
+    #[test]
+    fn replacing_ranks_the_new_entries() {
+        let mut standings = Standings::default();
+
+        standings.insert("stale".to_owned(), entry(9.0, 1.0));
+        standings.replace(BTreeMap::from([
+            ("bob".to_owned(), entry(2.0, 1.0)),
+            ("carol".to_owned(), entry(4.0, 1.0)),
+            ("nan".to_owned(), entry(f64::NAN, 1.0))
+        ]));
+
+        assert_eq!(ranked(&standings), vec![("carol", 4.0), ("bob", 2.0)]);
+        assert!(standings.get("nan").is_none());
+    }
+}
@@ -0,0 +1,13 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static FOCUS_LOST: AtomicBool = AtomicBool::new(false);
+
+// called by plugin.js when the browser tab is hidden or the window loses focus
+#[unsafe(no_mangle)]
+pub extern "C" fn _window_focus_lost() {
+    FOCUS_LOST.store(true, Ordering::Relaxed);
+}
+
+pub fn take_focus_lost() -> bool {
+    FOCUS_LOST.swap(false, Ordering::Relaxed)
+}
@@ -2,43 +2,62 @@ use std::time::Duration;
 use std::vec::Vec;
 
 use macroquad::audio::*;
+use macroquad::input::KeyCode;
 use macroquad::miniquad::conf::Platform;
 use macroquad::prelude::*;
 use macroquad::time::draw_fps;
-use macroquad::rand::RandGenerator;
 
 use macroquad::ui::*;
 
 mod sprite;
-mod collectable;
 mod background_pass;
+mod replay_viewer;
+mod ghost;
+mod config_watcher;
+mod daily;
+mod leaderboard_panel;
 
 use sprite::*;
-use collectable::*;
 use background_pass::*;
+use replay_viewer::*;
+use ghost::*;
+use config_watcher::*;
+use daily::*;
+use leaderboard_panel::*;
 
 use crate::math::pixel_space;
 use crate::math::Bounds2D;
-use crate::timer::Timer;
+use crate::simulation::*;
+use crate::timer::{GameClock, Timer};
 use crate::client::*;
+use crate::focus::take_focus_lost;
+use crate::settings::Settings;
+use crate::input::*;
+use crate::storage;
 
 pub fn window_config() -> Conf {
+     let settings = Settings::load();
+
      Conf {
         window_title: "Speedy Jumper".to_owned(),
-        fullscreen: false,
+        fullscreen: settings.fullscreen,
         window_resizable: true,
         window_width: 1280,
         window_height: 720,
-        platform: Platform { swap_interval: Some(1), ..Default::default() },
+        platform: Platform { swap_interval: Some(if settings.vsync { 1 } else { 0 }), ..Default::default() },
         ..Default::default()
      }
  }
 
 
+#[derive(Clone, Copy, PartialEq)]
 enum GameState {
     Menu, 
     Playing, 
-    EndScreen
+    Paused,
+    EndScreen,
+    Replay,
+    Settings
 }
 
 #[derive(Clone)]
@@ -47,43 +66,129 @@ struct GameResources {
     pub death_audio: Sound,
     pub start_audio: Sound, 
     pub soundtrack: Sound, 
-    pub string_buffer: String
+    pub string_buffer: String,
+    pub seed_buffer: String
+}
+
+// daily runs are always on Normal and never touch the personal best ghost, which belongs to random seeds
+#[derive(Clone, PartialEq)]
+enum RunMode {
+    Standard,
+    // the date of the challenge being played
+    DailyOfficial(String),
+    DailyPractice(String)
+}
+
+// a run waiting for the leaderboard to accept the player's name
+enum PendingRun {
+    // the requested seed
+    Standard(Option<u64>),
+    DailyOfficial(DailyChallenge)
 }
 
 struct Game {
     game_state: GameState, 
-    player: Sprite,
-    jump_boosts: Vec<JumpBoost>,
-    jump_boost_timer: Timer,
+    run_mode: RunMode,
+    pending_run: Option<PendingRun>,
+    world: World,
+    sprite: Sprite,
+    input: InputFrame,
+    recording: Replay,
+    // set when the tuning changed mid-run, the recording can't be played back the way the run went any more
+    recording_discarded: bool,
+    replay_viewer: Option<ReplayViewer>,
+    replay_return_state: GameState,
+    replay_message: Option<String>,
+    ghost: Option<Ghost>,
+    ghost_recording: Vec<Vec2>,
+    new_personal_best: bool,
+    clock: GameClock,
     dead_timer: Timer, 
-    is_dead: bool,  
     resources: GameResources, 
-    start_time: f64, 
     time_played: f64, 
+    accumulator: f64,
     client: Client,
-    show_name_already_exists: bool, 
-    show_name_timer: Timer
+    leaderboard_panel: LeaderboardPanel,
+    requested_seed: Option<u64>,
+    menu_error: Option<&'static str>, 
+    menu_error_timer: Timer,
+    // whether today's official daily attempt is used, read once on entering the menu instead of every frame
+    daily_attempt_used: Option<bool>,
+    settings: Settings,
+    settings_return_state: GameState,
+    rebinding: Option<Action>,
+    input_reader: InputReader,
+    config: GameConfig,
+    config_watcher: ConfigWatcher
 }
 
-pub static RANDOM: RandGenerator = RandGenerator::new();
-
 static SOUND_EFFECT_VOLUME_RATIO: f32 = 0.8;
 static SOUNDTRACK_VOLUME_RATIO: f32 = 0.1;
 
-static SPRITE_LARGE_VIEW_RADIUS: f32 = 600.0 / 1280.0;
-static SPRITE_SMALL_VIEW_RADIUS: f32 = 100.0 / 720.0;
+static GHOST_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.35);
+
+const MAX_FRAME_TIME: f64 = 0.25;
+
+static TOUCH_CONTROL_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.35);
+
+// menus keep at least this much room around widgets and never make them smaller than a fingertip
+const SCREEN_MARGIN: f32 = 16.0;
+const MIN_TOUCH_TARGET: f32 = 64.0;
+
+// how far ahead the trajectory preview looks and how many ticks apart its dots are
+const TRAJECTORY_PREVIEW_TICKS: usize = 96;
+const TRAJECTORY_DOT_SPACING: usize = 6;
+
+const RETICLE_RADIUS: f32 = 14.0;
+
+const END_SCREEN_BEST_SHOWN: usize = 5;
+
+// distances from the player center, in screen units, of the keyboard aim arrow
+const AIM_INDICATOR_START: f32 = 0.06;
+const AIM_INDICATOR_END: f32 = 0.12;
+
+// a frame this long while playing means the window was minimized, hidden or stalled
+const AUTO_PAUSE_FRAME_TIME: f64 = 0.5;
+
+const REPLAY_FILE: &str = "replay.sjr";
+const RECORDING_DISCARDED: &str = "The tuning changed during this run, it has no replay";
+const REPLAY_SEEK_TICKS: usize = 120;
 
 pub async fn run() {
-    let game_resources = create_game_resources().await;
-    let mut game_info  = create_game(game_resources, GameState::Menu, Client::empty()).await;
+    let clock = GameClock::new();
+    let config = load_game_config().await;
+    let game_resources = create_game_resources(&clock, &config).await;
+    let mut game_info  = create_game(game_resources, GameState::Menu, Client::new(default_backend()), None, clock, config).await;
     
     set_default_camera();
 
-    let mut delta_time = 0.0;
+    let mut last_frame_time = get_time();
+    let mut cursor_visible = true;
 
 
     loop {
-        let timer = Timer::new();
+        let frame_time = get_time();
+        let delta_time = game_info.clock.advance(frame_time - last_frame_time);
+        last_frame_time = frame_time;
+
+        game_info.input_reader.poll_devices();
+        game_info.client.update();
+
+        if let Some(config) = game_info.config_watcher.poll() {
+            apply_game_config(&mut game_info, config);
+        }
+
+        // the reticle replaces the system cursor while playing
+        let show_cursor = game_info.game_state != GameState::Playing || !game_info.settings.show_reticle;
+
+        if show_cursor != cursor_visible {
+            show_mouse(show_cursor);
+            cursor_visible = show_cursor;
+        }
+
+        if game_info.game_state != GameState::Menu {
+            game_info.daily_attempt_used = None;
+        }
 
         match game_info.game_state {
             GameState::Menu => {
@@ -92,224 +197,707 @@ pub async fn run() {
             GameState::Playing => {
                 playing_state(&mut game_info, delta_time);
             }, 
+            GameState::Paused => {
+                paused_state(&mut game_info);
+            }, 
             GameState::EndScreen => {
                 end_screen_state(&mut game_info).await;
             },
+            GameState::Replay => {
+                replay_state(&mut game_info, delta_time);
+            },
+            GameState::Settings => {
+                settings_state(&mut game_info);
+            },
         }
 
         next_frame().await;
-        delta_time = timer.elapsed().as_secs_f64();
     }
 }
 
-async fn new_sprite() -> Sprite {
-    Sprite::new(
-        "character".to_owned(), 
-        Vec2::new(0.5, 0.5),
-        Vec2::new(150.0 / 1280.0, 150.0 / 720.0), 
-        SPRITE_LARGE_VIEW_RADIUS).await
+fn random_seed() -> u64 {
+    (miniquad::date::now() * 1000.0) as u64
 }
 
-async fn create_game(mut game_resources: GameResources, game_state: GameState, client: Client) -> Game {
-    game_resources.background_pass.reset();
+async fn create_game(mut game_resources: GameResources, game_state: GameState, client: Client, requested_seed: Option<u64>, clock: GameClock, config: GameConfig) -> Game {
+    game_resources.background_pass.reset(&clock);
+
+    let settings = Settings::load();
+    let world = World::new(requested_seed.unwrap_or_else(random_seed), &settings.difficulty.apply(&config));
 
     Game {
         game_state,
+        run_mode: RunMode::Standard,
+        pending_run: None,
 
-        player: new_sprite().await,
+        recording: Replay::new(world.seed(), settings.difficulty, &settings.difficulty.apply(&config)),
+        recording_discarded: false,
+        replay_viewer: None,
+        replay_return_state: GameState::Menu,
+        replay_message: None,
 
-        jump_boosts: Vec::new(),
-        jump_boost_timer: Timer::new(),
+        ghost: Ghost::load(settings.difficulty),
+        ghost_recording: Vec::new(),
+        new_personal_best: false,
 
-        dead_timer: Timer::new(),
-        is_dead: false,
+        world,
+        sprite: Sprite::new("character".to_owned()).await,
+        input: InputFrame::default(),
+
+        dead_timer: Timer::new(&clock),
+        menu_error_timer: Timer::new(&clock),
+        clock,
 
         resources: game_resources,
 
-        start_time: get_time(),
         time_played: 0.0, 
+        accumulator: 0.0,
         client,
-        show_name_already_exists: false,
-        show_name_timer: Timer::new()
+        leaderboard_panel: LeaderboardPanel::default(),
+        requested_seed,
+        menu_error: None,
+        daily_attempt_used: None,
+        settings,
+        settings_return_state: GameState::Menu,
+        rebinding: None,
+        input_reader: InputReader::new(),
+        config,
+        config_watcher: ConfigWatcher::new()
     }
+}
+
+// A running world picks up the new values immediately, which is what makes hot reloading useful for tuning.
+// The run in progress then played with two tunings, so no replay of it can be trusted.
+fn apply_game_config(game_info: &mut Game, config: GameConfig) {
+    if run_in_progress(game_info) && !game_info.recording_discarded {
+        info!("the tuning changed mid-run, this run won't have a replay");
+        game_info.recording_discarded = true;
+    }
+
+    game_info.world.set_config(&run_difficulty(game_info).apply(&config));
+    game_info.resources.background_pass.set_stage_interval(config.stage_interval);
+    game_info.config = config;
 } 
 
-async fn reset_game(game_info: &mut Game) {
-    game_info.resources.background_pass.reset();
-    game_info.player = new_sprite().await;
-    game_info.jump_boosts = Vec::new();
-    game_info.jump_boost_timer = Timer::new();
-    game_info.dead_timer = Timer::new();
-    game_info.is_dead = false;
-    game_info.start_time = get_time();
+// also while the settings are open over a paused run
+fn run_in_progress(game_info: &Game) -> bool {
+    match game_info.game_state {
+        GameState::Playing | GameState::Paused => true,
+        GameState::Settings => game_info.settings_return_state == GameState::Paused,
+        _ => false
+    }
+}
+
+fn reset_game(game_info: &mut Game) {
+    game_info.resources.background_pass.reset(&game_info.clock);
+    let difficulty = run_difficulty(game_info);
+
+    game_info.world = World::new(game_info.requested_seed.unwrap_or_else(random_seed), &difficulty.apply(&game_info.config));
+    game_info.input = InputFrame::default();
+    game_info.input_reader.reset();
+    game_info.recording = Replay::new(game_info.world.seed(), difficulty, &difficulty.apply(&game_info.config));
+    game_info.recording_discarded = false;
+    game_info.replay_viewer = None;
+    game_info.replay_message = None;
+    game_info.ghost_recording = Vec::new();
+    game_info.new_personal_best = false;
+    game_info.dead_timer = Timer::new(&game_info.clock);
     game_info.time_played = 0.0;
-    game_info.show_name_already_exists = false;
+    game_info.accumulator = 0.0;
+    game_info.menu_error = None;
 }
 
 
-async fn create_game_resources() -> GameResources {
+async fn create_game_resources(clock: &GameClock, config: &GameConfig) -> GameResources {
     GameResources {
-        background_pass: BackgroundPass::new().await,
+        background_pass: BackgroundPass::new(clock, config.stage_interval).await,
         death_audio: load_sound("assets/fail.wav").await.unwrap(),
         start_audio: load_sound("assets/game_start.wav").await.unwrap(), 
         soundtrack: load_sound("assets/colorful_potions.wav").await.unwrap(),
-        string_buffer: String::new()
+        string_buffer: String::new(),
+        seed_buffer: String::new()
     }
 }
 
+// sampled once per rendered frame, a jump press stays latched until a simulation tick consumes it
+fn apply_controls(game_info: &mut Game, controls: &Controls) {
+    game_info.input.aim = controls.aim;
+    game_info.input.jump |= controls.jump;
+}
+
 fn playing_state(game_info: &mut Game, delta_time: f64) {
+    let stalled = delta_time > AUTO_PAUSE_FRAME_TIME && !game_info.recording.frames().is_empty();
+
+    let controls = game_info.input_reader.read(
+            &game_info.settings.bindings, 
+            game_info.settings.aim_mode, 
+            game_info.world.player.get_bounds().get_center(), 
+            delta_time as f32);
+
+    if controls.pause || take_focus_lost() || stalled {
+        pause_game(game_info);
+        draw_paused_world(game_info);
+        return;
+    }
+
+    if controls.restart {
+        restart_game(game_info);
+        return;
+    }
+
+    apply_controls(game_info, &controls);
+
+    game_info.accumulator += delta_time.min(MAX_FRAME_TIME);
+
+    while game_info.accumulator >= SIMULATION_TIMESTEP {
+        simulation_tick(game_info);
+        game_info.accumulator -= SIMULATION_TIMESTEP;
+    }
+
+    let alpha = (game_info.accumulator / SIMULATION_TIMESTEP) as f32;
+
     game_info.resources.background_pass.render(
+            &game_info.clock, 
             delta_time, 
-            game_info.start_time, 
-            &game_info.player);
+            &game_info.world.player, 
+            alpha);
 
-    draw_fps();
-    draw_boost_count(game_info);
-    draw_time(game_info);
+    if game_info.settings.show_fps {
+        draw_fps();
+    }
 
-    update_entities(game_info, delta_time);
-    resolve_collisions(game_info);
+    draw_boost_count(&game_info.world, game_info.settings.hud_scale);
+    draw_time(&game_info.world, game_info.settings.hud_scale);
+    draw_best_time_delta(game_info);
 
-    draw_entities(game_info);
+    draw_ghost(game_info, alpha, controls.look_at);
+    draw_entities(&game_info.world, &game_info.sprite, alpha, controls.look_at);
 
-    cleanup_boosts(game_info);
-    spawn_boosts(game_info);
+    if !game_info.world.is_dead {
+        if game_info.settings.trajectory_preview {
+            draw_trajectory_preview(&game_info.world, controls.aim);
+        }
 
-    if !game_info.is_dead {
-        game_info.client.register_time(get_time() - game_info.start_time);
-    }
+        if let Some(direction) = game_info.input_reader.aim_direction(game_info.settings.aim_mode) {
+            draw_aim_indicator(&game_info.world, alpha, direction);
+        }
 
-    if game_info.is_dead && game_info.dead_timer.has_elapsed(Duration::from_secs(2)) {
-        game_info.game_state = GameState::EndScreen;
+        if game_info.settings.show_reticle {
+            draw_reticle(controls.look_at, game_info.world.player.cooldown_progress());
+        }
     }
 
-    if !game_info.is_dead && !game_info.player.get_bounds().intersects(screen_bounds()) {
-        game_info.is_dead = true;
-        game_info.dead_timer.reset();
+    if let Some(touch_controls) = game_info.input_reader.touch_controls() {
+        draw_touch_controls(touch_controls);
+    }
 
-        game_info.time_played = get_time() - game_info.start_time;
+    if !game_info.world.is_dead && !matches!(game_info.run_mode, RunMode::DailyPractice(_)) {
+        game_info.client.register_time(game_info.world.time_survived());
+    }
 
-        stop_sound(&game_info.resources.soundtrack);
-        play_sound(&game_info.resources.death_audio, PlaySoundParams { looped: false, volume: SOUND_EFFECT_VOLUME_RATIO });
+    if game_info.world.is_dead && game_info.dead_timer.has_elapsed(&game_info.clock, Duration::from_secs(2)) {
+        game_info.game_state = GameState::EndScreen;
     }
 
     draw_leaderboard(game_info);
 }
 
+// every timer reads the game clock, so pausing it freezes the run; only the soundtrack plays on its own
+fn pause_game(game_info: &mut Game) {
+    game_info.game_state = GameState::Paused;
+    game_info.clock.pause();
 
-fn screen_bounds() -> Bounds2D {
-    Bounds2D::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0))
+    stop_sound(&game_info.resources.soundtrack);
 }
 
-fn draw_boost_count(game_info: &mut Game) {
-    draw_text(&format!("Boost Count: {}", game_info.player.boost_counter), 0.0, 16.0 * 3.0, 32.0, WHITE); 
+fn resume_game(game_info: &mut Game) {
+    game_info.game_state = GameState::Playing;
+    game_info.clock.resume();
+
+    // macroquad sounds can't be paused or seeked, so the soundtrack starts over
+    play_sound(&game_info.resources.soundtrack, PlaySoundParams { looped: true, volume: soundtrack_volume(&game_info.settings) });
+
+    // drop anything pressed on the overlay so it doesn't leak into the run
+    game_info.input.jump = false;
+    take_focus_lost();
 }
 
-fn draw_time(game_info: &mut Game) {
-    draw_text(&format!("Time: {:.2}s", get_time() - game_info.start_time), 0.0, 16.0 * 6.0, 32.0, WHITE); 
+fn run_difficulty(game_info: &Game) -> Difficulty {
+    match game_info.run_mode {
+        RunMode::Standard => game_info.settings.difficulty,
+        _ => Difficulty::Normal
+    }
 }
 
-fn cleanup_boosts(game_info: &mut Game) {
-    let screen_bounds = screen_bounds();
-    let player_bounds = game_info.player.get_bounds();
+fn current_board(game_info: &Game) -> Board {
+    match &game_info.run_mode {
+        RunMode::Standard => Board::Standard(game_info.settings.difficulty),
+        RunMode::DailyOfficial(date) | RunMode::DailyPractice(date) => Board::Daily(date.clone())
+    }
+}
 
-    game_info.jump_boosts.retain(|boost| boost.bounds.intersects(screen_bounds) && !boost.bounds.intersects(player_bounds));
+fn personal_ghost(game_info: &Game) -> Option<&Ghost> {
+    match game_info.run_mode {
+        RunMode::Standard => game_info.ghost.as_ref(),
+        _ => None
+    }
 }
 
-fn random_boost_acceleration(game_info: &Game) -> Vec2 {
-    let time_elapsed = get_time() - game_info.start_time;
-    let mut upper_bound = clamp(1.0 - 1.0 / (time_elapsed * 0.05), 0.2, 1.0) as f32;
-    upper_bound *= 1.0;
+// the official daily attempt is a single run, anything after it is practice
+fn restart_game(game_info: &mut Game) {
+    if let RunMode::DailyOfficial(date) = &game_info.run_mode {
+        game_info.run_mode = RunMode::DailyPractice(date.clone());
+        game_info.client.leave();
+    }
 
-    Vec2::new(RANDOM.gen_range(-upper_bound, upper_bound), RANDOM.gen_range(-upper_bound, upper_bound))
+    begin_run(game_info);
 }
 
-fn gen_random_boost(game_info: &Game) -> JumpBoost {
-    loop {
-        let boost_position = Vec2::new(RANDOM.gen_range(0.0, 1.0), RANDOM.gen_range(0.0, 1.0));
-        let boost_size = Vec2::splat(RANDOM.gen_range(25.0 / 1280.0, 50.0 / 1280.0));
-        
-        if !Bounds2D::new(boost_position, boost_size).intersects(game_info.player.get_bounds()) {
-            break JumpBoost::new(
-                    boost_position, 
-                    boost_size, 
-                    RANDOM.gen_range(0.0 as f64, 1.0 as f64).round() == 1.0, 
-                    random_boost_acceleration(game_info))
+fn start_daily_challenge(game_info: &mut Game) {
+    let daily = DailyChallenge::today();
+
+    if !daily.official_attempt_used() {
+        let name = game_info.resources.string_buffer.clone();
+
+        match game_info.client.join(&name, &Board::Daily(daily.date())) {
+            Ok(()) => game_info.pending_run = Some(PendingRun::DailyOfficial(daily)),
+            Err(error) => show_menu_error(game_info, error.message())
         }
+
+        return;
+    }
+
+    game_info.run_mode = RunMode::DailyPractice(daily.date());
+    game_info.client.leave();
+    game_info.requested_seed = Some(daily.seed());
+
+    begin_run(game_info);
+}
+
+// starts the run the player asked for once their name is accepted
+fn poll_pending_run(game_info: &mut Game) {
+    if game_info.pending_run.is_none() {
+        return;
     }
-}   
 
-fn spawn_boosts(game_info: &mut Game) {
-    let max_boosts = 15;
-    let max_boosts_add = 7;
+    let Some(answer) = game_info.client.poll_join() else {
+        return;
+    };
 
-    const BOOST_SPAWN_COOLDOWN: Duration = Duration::new(1, 0);
+    let Some(pending_run) = game_info.pending_run.take() else {
+        return;
+    };
 
-    if game_info.jump_boosts.len() >= max_boosts || !game_info.jump_boost_timer.has_elapsed(BOOST_SPAWN_COOLDOWN) {
+    if let Err(error) = answer {
+        show_menu_error(game_info, error.message());
         return;
     }
 
-    game_info.jump_boost_timer.reset();
+    match pending_run {
+        PendingRun::Standard(requested_seed) => {
+            game_info.requested_seed = requested_seed;
+            game_info.run_mode = RunMode::Standard;
+
+            restart_game(game_info);
+        }
+        PendingRun::DailyOfficial(daily) => {
+            // the attempt only counts once the run can actually be submitted
+            if let Err(error) = daily.use_official_attempt() {
+                error!("failed to record the daily attempt: {error}");
+            }
 
-    let boosts_to_add = max_boosts_add.min(max_boosts - game_info.jump_boosts.len());
+            game_info.run_mode = RunMode::DailyOfficial(daily.date());
+            game_info.requested_seed = Some(daily.seed());
 
-    for _ in 0..boosts_to_add {
-        game_info.jump_boosts.push(gen_random_boost(&game_info));
+            begin_run(game_info);
+        }
     }
+}
+
+fn begin_run(game_info: &mut Game) {
+    stop_sound(&game_info.resources.soundtrack);
+
+    game_info.game_state = GameState::Playing;
+    restart_clock(&mut game_info.clock);
+    reset_game(game_info);
+
+    play_sound(&game_info.resources.start_audio, PlaySoundParams { looped: false, volume: effects_volume(&game_info.settings) });
+    play_sound(&game_info.resources.soundtrack, PlaySoundParams { looped: true, volume: soundtrack_volume(&game_info.settings) });
+}
+
+fn draw_paused_world(game_info: &mut Game) {
+    game_info.resources.background_pass.render(
+            &game_info.clock, 
+            0.0, 
+            &game_info.world.player, 
+            0.0);
+
+    draw_boost_count(&game_info.world, game_info.settings.hud_scale);
+    draw_time(&game_info.world, game_info.settings.hud_scale);
+    draw_entities(&game_info.world, &game_info.sprite, 0.0, game_info.world.player.get_bounds().get_center());
+
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+}
+
+fn paused_state(game_info: &mut Game) {
+    draw_paused_world(game_info);
+
+    let text = "Paused";
+    let font_size = 32.0;
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let x = screen_width() / 2.0 - text_dimensions.width / 2.0;
+    let y = 32.0;
+
+    draw_text(text, x, y, font_size, WHITE);
+
+    // sized from the widest label so the overlay buttons line up
+    let text_dimensions = measure_text("Quit to Menu", None, font_size as u16, 1.0);
+    let button_size = fit_widget(Vec2::new(text_dimensions.width * 1.5, text_dimensions.height * 3.0));
+    let x = screen_width() / 2.0 - button_size.x / 2.0;
+
+    if widgets::Button::new("Resume")
+        .position(Vec2::new(x, 128.0))
+        .size(button_size)
+        .ui(&mut root_ui())
+        || game_info.input_reader.is_pressed(&game_info.settings.bindings, Action::Pause)
+        {
+            resume_game(game_info);
+            return;
+        }
+
+    if widgets::Button::new("Restart")
+        .position(Vec2::new(x, 256.0))
+        .size(button_size)
+        .ui(&mut root_ui())
+        || game_info.input_reader.is_pressed(&game_info.settings.bindings, Action::Restart)
+        {
+            restart_game(game_info);
+            return;
+        }
+
+    if widgets::Button::new("Settings")
+        .position(Vec2::new(x, 384.0))
+        .size(button_size)
+        .ui(&mut root_ui())
+        {
+            open_settings(game_info);
+            return;
+        }
+
+    if widgets::Button::new("Quit to Menu")
+        .position(Vec2::new(x, 512.0))
+        .size(button_size)
+        .ui(&mut root_ui())
+        {
+            stop_sound(&game_info.resources.soundtrack);
 
+            game_info.clock.resume();
+            game_info.game_state = GameState::Menu;
+            game_info.client.leave();
+        }
+}
+
+fn open_settings(game_info: &mut Game) {
+    game_info.settings_return_state = game_info.game_state;
+    game_info.game_state = GameState::Settings;
 }
 
-fn update_entities(game_info: &mut Game, delta_time: f64) {
-    for boost in &mut game_info.jump_boosts {
-        boost.update(delta_time);
+fn settings_state(game_info: &mut Game) {
+    clear_background(BLACK);
+
+    // the click that started rebinding happened last frame, so the first input seen here is the new binding
+    let rebound = game_info.rebinding.and_then(|action| game_info.input_reader.binding_pressed_this_frame().map(|binding| (action, binding)));
+
+    if let Some((action, binding)) = rebound {
+        game_info.settings.bindings.rebind(action, binding);
+        game_info.rebinding = None;
+        return;
     }
 
-    if !game_info.is_dead {
-        game_info.player.update(delta_time);
+    let text = "Settings";
+    let font_size = 32.0;
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let x = screen_width() / 2.0 - text_dimensions.width / 2.0;
+    let y = 32.0;
+
+    draw_text(text, x, y, font_size, WHITE);
+
+    let fullscreen = game_info.settings.fullscreen;
+    let settings = &mut game_info.settings;
+    let rebinding = &mut game_info.rebinding;
+
+    let window_size = Vec2::new(500.0_f32.min(screen_width() - SCREEN_MARGIN * 2.0), 520.0);
+    let window_position = Vec2::new(screen_width() / 2.0 - window_size.x / 2.0, 64.0);
+
+    widgets::Window::new(hash!(), window_position, window_size)
+        .titlebar(false)
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            ui.slider(hash!(), "Master volume", 0.0..1.0, &mut settings.master_volume);
+            ui.slider(hash!(), "Music volume", 0.0..1.0, &mut settings.music_volume);
+            ui.slider(hash!(), "Effects volume", 0.0..1.0, &mut settings.effects_volume);
+            ui.checkbox(hash!(), "Mute", &mut settings.muted);
+            ui.checkbox(hash!(), "Fullscreen", &mut settings.fullscreen);
+            ui.checkbox(hash!(), "VSync (applies on restart)", &mut settings.vsync);
+            ui.checkbox(hash!(), "Show FPS", &mut settings.show_fps);
+            ui.slider(hash!(), "HUD scale", 0.5..2.0, &mut settings.hud_scale);
+            ui.checkbox(hash!(), "Jump trajectory preview", &mut settings.trajectory_preview);
+            ui.checkbox(hash!(), "Aim reticle", &mut settings.show_reticle);
+
+            ui.separator();
+
+            let mut aim_mode = settings.aim_mode.index();
+            ui.combo_box(hash!(), "Aiming", &AIM_MODE_NAMES, &mut aim_mode);
+            settings.aim_mode = AimMode::from_index(aim_mode);
+
+            for action in REBINDABLE_ACTIONS {
+                let bound = match rebinding {
+                    Some(waiting) if *waiting == action => "press a key or mouse button...".to_owned(),
+                    _ => settings.bindings.describe(action)
+                };
+
+                ui.label(None, &format!("{}: {}", action.name(), bound));
+                ui.same_line(330.0);
+
+                if ui.button(None, format!("Rebind {}", action.name())) {
+                    *rebinding = Some(action);
+                }
+            }
+
+            if ui.button(None, "Reset controls") {
+                settings.bindings = Bindings::default();
+                *rebinding = None;
+            }
+        });
+
+    if settings.fullscreen != fullscreen {
+        set_fullscreen(settings.fullscreen);
     }
+
+    let text = "Back";
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let button_size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
+
+    let x = screen_width() / 2.0 - button_size.x / 2.0;
+    let y = window_position.y + window_size.y + 32.0;
+
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y))
+        .size(button_size)
+        .ui(&mut root_ui())
+        || (game_info.rebinding.is_none() && is_key_pressed(KeyCode::Escape))
+        {
+            game_info.rebinding = None;
+
+            if let Err(error) = game_info.settings.save() {
+                error!("failed to save settings: {error}");
+            }
+
+            game_info.game_state = game_info.settings_return_state;
+        }
 }
 
-fn draw_entities(game_info: &Game) {
-    let player_position = game_info.player
-        .get_bounds() 
-        .get_center();
+fn simulation_tick(game_info: &mut Game) {
+    game_info.recording.record(game_info.input);
+
+    let events = game_info.world.step(&game_info.input).to_vec();
+    game_info.input.jump = false;
+
+    if !game_info.world.is_dead {
+        game_info.ghost_recording.push(game_info.world.player.get_bounds().get_position());
+    }
+
+    for event in events {
+        game_info.sprite.handle_event(&event, game_info.settings.effects_volume());
+
+        if event == WorldEvent::Died {
+            game_info.dead_timer.reset(&game_info.clock);
+            game_info.time_played = game_info.world.time_survived();
 
+            update_personal_best(game_info);
 
-    for boost in &game_info.jump_boosts {
-        if player_position.distance(boost.bounds.get_center()) <= game_info.player.view_radius {
-            boost.draw();
+            if !matches!(game_info.run_mode, RunMode::DailyPractice(_)) {
+                game_info.client.finish_run(game_info.time_played, game_info.world.seed());
+            }
+
+            stop_sound(&game_info.resources.soundtrack);
+            play_sound(&game_info.resources.death_audio, PlaySoundParams { looped: false, volume: effects_volume(&game_info.settings) });
         }
     }
+}
 
-    if !game_info.is_dead {
-        game_info.player.draw();
+fn update_personal_best(game_info: &mut Game) {
+    if game_info.run_mode != RunMode::Standard {
+        return;
     }
+
+    let best_time = game_info.ghost.as_ref().map_or(0.0, |ghost| ghost.best_time());
+
+    if game_info.time_played <= best_time {
+        return;
+    }
+
+    let ghost = Ghost::new(game_info.time_played, std::mem::take(&mut game_info.ghost_recording));
+
+    if let Err(error) = ghost.save(game_info.settings.difficulty) {
+        error!("failed to save personal best ghost: {error}");
+    }
+
+    game_info.ghost = Some(ghost);
+    game_info.new_personal_best = true;
 }
 
-fn resolve_collisions(game_info: &mut Game) {
-    if game_info.is_dead {
+// the ghost is only drawn, it never takes part in the simulation
+fn draw_ghost(game_info: &Game, alpha: f32, look_at: Vec2) {
+    let Some(ghost) = personal_ghost(game_info) else {
+        return;
+    };
+
+    if game_info.world.is_dead {
         return;
     }
 
-    for boost in &game_info.jump_boosts {
-        if boost.bounds.intersects(game_info.player.get_bounds()) {
-            if boost.hurtful {
-                game_info.player.boost_counter -= 1;
-                game_info.player.view_radius = SPRITE_SMALL_VIEW_RADIUS;
-            }
-            else {
-                game_info.player.boost_counter += 2;
-                game_info.player.view_radius = SPRITE_LARGE_VIEW_RADIUS;
-            }
+    let tick = game_info.ghost_recording.len();
+
+    if let Some(position) = ghost.position(tick, alpha) {
+        let bounds = Bounds2D::new(position, game_info.world.player.get_bounds().get_size());
+        game_info.sprite.draw_at(bounds, look_at, GHOST_COLOR);
+    }
+}
+
+fn draw_best_time_delta(game_info: &Game) {
+    let Some(ghost) = personal_ghost(game_info) else {
+        return;
+    };
+
+    // the ghost is alive for exactly best_time, so until then the only news is how much of it is left
+    let remaining = ghost.best_time() - game_info.world.time_survived();
+
+    let progress = if remaining > 0.0 {
+        format!("{remaining:.2}s remaining to beat best")
+    } else {
+        format!("beaten by {:.2}s", -remaining)
+    };
+
+    draw_hud_line(&format!("Best: {:.2}s ({progress})", ghost.best_time()), 3.0, game_info.settings.hud_scale); 
+}
+
+fn draw_boost_count(world: &World, hud_scale: f32) {
+    draw_hud_line(&format!("Boost Count: {}", world.player.boost_counter), 1.0, hud_scale); 
+}
+
+fn draw_time(world: &World, hud_scale: f32) {
+    draw_hud_line(&format!("Time: {:.2}s", world.time_survived()), 2.0, hud_scale); 
+}
+
+fn draw_hud_line(text: &str, line: f32, hud_scale: f32) {
+    draw_text(text, 0.0, 16.0 * 3.0 * line * hud_scale, 32.0 * hud_scale, WHITE); 
+}
+
+fn effects_volume(settings: &Settings) -> f32 {
+    SOUND_EFFECT_VOLUME_RATIO * settings.effects_volume()
+}
+
+fn soundtrack_volume(settings: &Settings) -> f32 {
+    SOUNDTRACK_VOLUME_RATIO * settings.music_volume()
+}
+
+fn draw_entities(world: &World, sprite: &Sprite, alpha: f32, look_at: Vec2) {
+    let player_position = world.player
+        .get_interpolated_bounds(alpha) 
+        .get_center();
+
+
+    for boost in &world.jump_boosts {
+        let bounds = boost.get_interpolated_bounds(alpha);
+
+        if player_position.distance(bounds.get_center()) <= world.player.view_radius {
+            draw_jump_boost(boost, bounds);
         }
     }
 
-    game_info.player.boost_counter = game_info.player.boost_counter.max(0);
+    if !world.is_dead {
+        sprite.draw(&world.player, alpha, look_at);
+    }
+}
+
+fn draw_aim_indicator(world: &World, alpha: f32, direction: Vec2) {
+    let player_position = world.player
+        .get_interpolated_bounds(alpha)
+        .get_center();
+
+    let start = pixel_space(player_position + direction * AIM_INDICATOR_START);
+    let end = pixel_space(player_position + direction * AIM_INDICATOR_END);
+
+    draw_line(start.x, start.y, end.x, end.y, 3.0, WHITE);
+    draw_circle(end.x, end.y, 5.0, WHITE);
+}
+
+fn draw_trajectory_preview(world: &World, aim: Vec2) {
+    let points = world.player.predict_jump(aim, SIMULATION_TIMESTEP, TRAJECTORY_PREVIEW_TICKS);
+
+    for (i, point) in points.iter().enumerate().step_by(TRAJECTORY_DOT_SPACING) {
+        // fades out along the arc since the prediction ignores boosts picked up on the way
+        let fade = 1.0 - i as f32 / points.len() as f32;
+        let point = pixel_space(*point);
+
+        draw_circle(point.x, point.y, 3.0, Color::new(1.0, 1.0, 1.0, 0.6 * fade));
+    }
+}
+
+// the ring fills up while the jump cooldown runs out and turns solid once a jump is possible
+fn draw_reticle(position: Vec2, cooldown_progress: f32) {
+    let position = pixel_space(position);
+
+    if cooldown_progress >= 1.0 {
+        draw_circle_lines(position.x, position.y, RETICLE_RADIUS, 3.0, WHITE);
+    } else {
+        draw_circle_lines(position.x, position.y, RETICLE_RADIUS, 3.0, Color::new(1.0, 1.0, 1.0, 0.25));
+        draw_arc(position.x, position.y, 32, RETICLE_RADIUS, -90.0, 3.0, 360.0 * cooldown_progress, WHITE);
+    }
+
+    draw_circle(position.x, position.y, 2.0, WHITE);
+}
+
+fn draw_touch_controls(touch_controls: &TouchControls) {
+    let pause = pause_button_bounds();
+    let bar_width = pause.w / 5.0;
+
+    draw_rectangle_lines(pause.x, pause.y, pause.w, pause.h, 3.0, TOUCH_CONTROL_COLOR);
+    draw_rectangle(pause.x + bar_width, pause.y + pause.h / 4.0, bar_width, pause.h / 2.0, TOUCH_CONTROL_COLOR);
+    draw_rectangle(pause.x + bar_width * 3.0, pause.y + pause.h / 4.0, bar_width, pause.h / 2.0, TOUCH_CONTROL_COLOR);
+
+    let jump = jump_button_center();
+
+    if touch_controls.is_jump_down() {
+        draw_circle(jump.x, jump.y, JUMP_BUTTON_RADIUS, TOUCH_CONTROL_COLOR);
+    }
+
+    draw_circle_lines(jump.x, jump.y, JUMP_BUTTON_RADIUS, 3.0, TOUCH_CONTROL_COLOR);
+
+    let text_dimensions = measure_text("JUMP", None, 32, 1.0);
+    draw_text("JUMP", jump.x - text_dimensions.width / 2.0, jump.y + text_dimensions.height / 2.0, 32.0, TOUCH_CONTROL_COLOR);
+
+    if let Some((origin, knob)) = touch_controls.stick() {
+        draw_circle_lines(origin.x, origin.y, STICK_RADIUS, 3.0, TOUCH_CONTROL_COLOR);
+        draw_circle(knob.x, knob.y, STICK_RADIUS / 3.0, TOUCH_CONTROL_COLOR);
+    }
+}
+
+fn draw_jump_boost(boost: &JumpBoost, bounds: Bounds2D) {
+    if boost.hurtful {
+        draw_rectangle_screen(bounds.get_position(), bounds.get_size(), RED); 
+    } else {
+        draw_rectangle_screen(bounds.get_position(), bounds.get_size(), GREEN);
+    }
 }
 
 async fn end_screen_state(game_info: &mut Game) {
     let fmt_text = format!("Stupid ahh guy bro only got {:.2}s", game_info.time_played);
-    let text = &fmt_text.as_str();
+    let text = fmt_text.as_str();
     let font_size = 32.0;
 
     let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
@@ -320,97 +908,362 @@ async fn end_screen_state(game_info: &mut Game) {
 
     draw_text(text, x, y, font_size, WHITE);
 
+    let mode = match &game_info.run_mode {
+        RunMode::Standard => game_info.recording.difficulty().name().to_owned(),
+        RunMode::DailyOfficial(date) => format!("Daily {date}"),
+        RunMode::DailyPractice(date) => format!("Daily {date} (practice)")
+    };
+
+    game_info.client.sync();
+
+    let mut fmt_text = format!("Seed: {}   {}", game_info.world.seed(), mode);
+
+    if let Some((rank, entries)) = game_info.client.rank(&current_board(game_info)) {
+        fmt_text += &format!("   Rank {rank} of {entries}");
+    }
+
+    if game_info.new_personal_best {
+        fmt_text += "   New personal best!";
+    }
+    let text = fmt_text.as_str();
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let x = screen_width() / 2.0 - text_dimensions.width / 2.0;
+    let y = 32.0 + text_dimensions.height + 16.0;
+
+    draw_text(text, x, y, font_size, WHITE);
+
     let text = "Play Again";
     let font_size = 32.0;
 
     let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
-    let text_width = text_dimensions.width;
+    let size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
 
-    let x = screen_width() / 2.0 - text_width * 3.0 / 2.0;
+    let x = screen_width() / 2.0 - size.x / 2.0;
     let y = 128.0;
 
     if widgets::Button::new(text)
         .position(Vec2::new(x, y))
-        .size(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0)
-        .ui(&mut *root_ui())
+        .size(size)
+        .ui(&mut root_ui())
         {
-            game_info.game_state = GameState::Playing;
-            reset_game(game_info).await;
-
-            play_sound(&game_info.resources.start_audio, PlaySoundParams { looped: false, volume: SOUND_EFFECT_VOLUME_RATIO });
-            play_sound(&game_info.resources.soundtrack, PlaySoundParams { looped: true, volume: SOUNDTRACK_VOLUME_RATIO });
+            restart_game(game_info);
         }
 
     let text = "Main Menu";
     let font_size = 32.0;
 
     let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
-    let text_width = text_dimensions.width;
+    let size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
 
-    let x = screen_width() / 2.0 - text_width * 3.0 / 2.0;
+    let x = screen_width() / 2.0 - size.x / 2.0;
     let y = 256.0;
 
     if widgets::Button::new(text)
         .position(Vec2::new(x, y))
-        .size(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0)
-        .ui(&mut *root_ui())
+        .size(size)
+        .ui(&mut root_ui())
         {
             game_info.game_state = GameState::Menu;
-            game_info.client.register_time(-1.0);
+            game_info.client.leave();
+        }
+
+    let text = "Watch Replay";
+    let font_size = 32.0;
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
+
+    let x = screen_width() / 2.0 - size.x / 2.0;
+    let y = 384.0;
+
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y))
+        .size(size)
+        .ui(&mut root_ui())
+        {
+            game_info.replay_message = if game_info.recording_discarded {
+                Some(RECORDING_DISCARDED.to_owned())
+            } else {
+                start_replay(game_info, game_info.recording.clone(), GameState::EndScreen).err()
+            };
+        }
+
+    let text = "Save Replay";
+    let font_size = 32.0;
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
+
+    let x = screen_width() / 2.0 - size.x / 2.0;
+    let y = 512.0;
+
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y))
+        .size(size)
+        .ui(&mut root_ui())
+        {
+            game_info.replay_message = if game_info.recording_discarded {
+                Some(RECORDING_DISCARDED.to_owned())
+            } else {
+                match storage::write(REPLAY_FILE, &game_info.recording.to_bytes()) {
+                    Ok(()) => Some("Replay saved".to_owned()),
+                    Err(error) => Some(error)
+                }
+            };
         }
+
+    if let Some(text) = &game_info.replay_message {
+        let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+
+        let x = screen_width() / 2.0 - text_dimensions.width / 2.0;
+        let y = 640.0;
+
+        draw_text(text, x, y, font_size, WHITE);
+    }
+
+    // beside the buttons in landscape, under them in portrait
+    let top = if screen_width() < screen_height() { 672.0 } else { 128.0 };
+    draw_best_runs(game_info, top);
 }
 
-fn draw_leaderboard(game_info: &mut Game) {
-    game_info.client.sync();
+// refused when the tuning changed since the run was recorded, it would play back differently
+fn start_replay(game_info: &mut Game, replay: Replay, return_state: GameState) -> Result<(), String> {
+    let config = replay.difficulty().apply(&game_info.config);
 
-    let title_text = "Leaderboard       ";
-    let title_dim = measure_text(title_text, None, 32, 1.0);
+    game_info.replay_viewer = Some(ReplayViewer::new(replay, &config)?);
+    game_info.replay_return_state = return_state;
+    game_info.game_state = GameState::Replay;
+    game_info.resources.background_pass.reset(&game_info.clock);
 
-    draw_text(title_text, screen_width() - title_dim.width, 0.0 + title_dim.height, 32.0, WHITE);
+    Ok(())
+}
 
-    let mut curr_y = title_dim.height;
-    let mut count = 0;
+fn exit_replay(game_info: &mut Game) {
+    game_info.replay_viewer = None;
+    game_info.game_state = game_info.replay_return_state;
 
-    let leaderboard = game_info.client.get_leaderboard();
+    game_info.clock.resume();
+    game_info.clock.set_time_scale(1.0);
+}
 
-    if leaderboard.is_empty() {
-        let text = "failed to connect to server";
-        let dim = measure_text(text, None, 32, 1.0);
+// runs can be restarted from the pause overlay, the new one must not start frozen
+fn restart_clock(clock: &mut GameClock) {
+    clock.resume();
+    clock.set_time_scale(1.0);
+}
+
+fn toggle_clock_pause(clock: &mut GameClock) {
+    if clock.is_paused() {
+        clock.resume();
+    } else {
+        clock.pause();
+    }
+}
 
-        draw_text(text, screen_width() - dim.width - 10.0, curr_y + dim.height + 10.0, 32.0, WHITE);
-        
+fn replay_state(game_info: &mut Game, mut delta_time: f64) {
+    let Some(viewer) = game_info.replay_viewer.as_mut() else {
+        exit_replay(game_info);
         return;
+    };
+
+    let bindings = &game_info.settings.bindings;
+    let input = &game_info.input_reader;
+
+    // pause toggles playback, jump steps a single tick while paused and aiming sideways seeks
+    if input.is_pressed(bindings, Action::Pause) {
+        toggle_clock_pause(&mut game_info.clock);
+    }
+
+    if input.is_pressed_ignoring_mouse(bindings, Action::Jump) && game_info.clock.is_paused() {
+        delta_time += game_info.clock.step(SIMULATION_TIMESTEP);
+    }
+
+    if input.is_pressed(bindings, Action::AimLeft) {
+        viewer.seek(viewer.tick().saturating_sub(REPLAY_SEEK_TICKS));
+    }
+
+    if input.is_pressed(bindings, Action::AimRight) {
+        viewer.seek(viewer.tick() + REPLAY_SEEK_TICKS);
     }
 
-    let max_name_width = leaderboard.iter()
-        .take(10)
-        .map(|(name, _)| measure_text(name, None, 32, 1.0).width)
-        .fold(0.0, f32::max);
+    for event in viewer.advance(delta_time) {
+        game_info.sprite.handle_event(&event, game_info.settings.effects_volume());
+    }
 
+    let world = viewer.world();
+    let alpha = viewer.alpha();
+    let look_at = world.player.get_bounds().get_center() + viewer.current_aim();
+
+    game_info.resources.background_pass.render(
+            &game_info.clock, 
+            delta_time, 
+            &world.player, 
+            alpha);
+
+    draw_boost_count(world, game_info.settings.hud_scale);
+    draw_time(world, game_info.settings.hud_scale);
+
+    draw_entities(world, &game_info.sprite, alpha, look_at);
+
+    let step_binding = bindings.get(Action::Jump).iter()
+        .find(|binding| !matches!(binding, Binding::Mouse(_)))
+        .map_or("unbound", Binding::name);
+
+    if draw_replay_controls(viewer, &mut game_info.clock, step_binding) {
+        exit_replay(game_info);
+    }
+}
+
+// returns true once the player asks to leave the replay
+fn draw_replay_controls(viewer: &mut ReplayViewer, clock: &mut GameClock, step_binding: &str) -> bool {
+    let font_size = 32.0;
     let padding = 10.0;
-    let score_x = screen_width() - padding;
-    let name_x = score_x - 100.0 - max_name_width; 
 
-    let mut vec: Vec<_> = leaderboard.iter().collect();
-    vec.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap()); 
+    let fmt_text = format!(
+        "Replay {:.2}s / {:.2}s  x{}{}", 
+        viewer.tick() as f64 * SIMULATION_TIMESTEP, 
+        viewer.tick_count() as f64 * SIMULATION_TIMESTEP, 
+        clock.time_scale(), 
+        if clock.is_paused() { format!("  (paused, {step_binding} to step)") } else { String::new() });
+    let text = fmt_text.as_str();
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    draw_text(text, screen_width() / 2.0 - text_dimensions.width / 2.0, 32.0, font_size, WHITE);
+
+    let button_height = 48.0;
+    let y = screen_height() - button_height - padding;
+    let mut x = padding;
+
+    let text = if clock.is_paused() { "Play" } else { "Pause" };
+    let button_width = measure_text("Pause", None, font_size as u16, 1.0).width + padding * 2.0;
 
-    for (name, score) in vec.iter().rev() {
-        if count == 10 {
-            break;
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y))
+        .size(Vec2::new(button_width, button_height))
+        .ui(&mut root_ui())
+        {
+            toggle_clock_pause(clock);
         }
 
-        count += 1;
+    x += button_width + padding;
+
+    for speed in REPLAY_SPEEDS {
+        let fmt_text = format!("x{speed}");
+        let text = fmt_text.as_str();
+
+        let button_width = measure_text(text, None, font_size as u16, 1.0).width + padding * 2.0;
+
+        if widgets::Button::new(text)
+            .position(Vec2::new(x, y))
+            .size(Vec2::new(button_width, button_height))
+            .selected(clock.time_scale() == speed)
+            .ui(&mut root_ui())
+            {
+                clock.set_time_scale(speed);
+            }
+
+        x += button_width + padding;
+    }
+
+    let text = "Exit";
+    let button_width = measure_text(text, None, font_size as u16, 1.0).width + padding * 2.0;
+    let exit_x = screen_width() - button_width - padding;
+
+    let exit = widgets::Button::new(text)
+        .position(Vec2::new(exit_x, y))
+        .size(Vec2::new(button_width, button_height))
+        .ui(&mut root_ui());
+
+    let timeline_width = (exit_x - padding - x).max(0.0);
+    let mut timeline = viewer.tick() as f32;
+
+    widgets::Group::new(hash!(), Vec2::new(timeline_width, button_height))
+        .position(Vec2::new(x, y))
+        .ui(&mut root_ui(), |ui| {
+            widgets::Slider::new(hash!(), 0.0..viewer.tick_count() as f32).ui(ui, &mut timeline);
+        });
+
+    if timeline as usize != viewer.tick() {
+        viewer.seek(timeline as usize);
+    }
+
+    exit || is_key_pressed(KeyCode::Escape)
+}
+
+fn draw_leaderboard(game_info: &mut Game) {
+    game_info.client.sync();
+
+    let board = current_board(game_info);
+
+    let leaderboard = game_info.client.get_leaderboard();
+
+    let source = PanelSource {
+        board: board.title(),
+        status: game_info.client.status(),
+        revision: leaderboard.layout_version()
+    };
+
+    let rows = leaderboard.ranked()
+        .filter_map(|(key, entry)| name_on_board(key, &board).map(|name| (name, entry.time, entry.live)));
+
+    if game_info.leaderboard_panel.is_laid_out_for(&source) {
+        game_info.leaderboard_panel.update_times(leaderboard.version(), rows.map(|(_, time, live)| (time, live)));
+    } else {
+        game_info.leaderboard_panel.lay_out(source, leaderboard.version(), rows);
+    }
+
+    game_info.leaderboard_panel.draw();
+}
+
+// the best finished runs on the finished run's board, with the player's own entry highlighted
+fn draw_best_runs(game_info: &Game, top: f32) {
+    let board = current_board(game_info);
+    let font_size = 24.0;
+    let padding = 8.0;
 
-        let name_dim = measure_text(name, None, 32, 1.0);
-        curr_y += name_dim.height + padding;
+    let title = match game_info.client.status() {
+        None => format!("Local best ({})", board.title()),
+        Some(_) => format!("Best ({})", board.title())
+    };
+    let title_dim = measure_text(&title, None, font_size as u16, 1.0);
 
-        let score_text = format!("{:.2}s", score);
-        let score_dim = measure_text(&score_text, None, 32, 1.0);
+    let x = SCREEN_MARGIN;
+    let mut y = top + title_dim.height;
 
-        draw_text(name, name_x + (max_name_width - name_dim.width), curr_y, 32.0, WHITE);
-        draw_text(&score_text, score_x - score_dim.width, curr_y, 32.0, WHITE);
+    draw_text(&title, x, y, font_size, WHITE);
+
+    let best_runs = game_info.client.get_leaderboard().ranked()
+        .filter(|(_, entry)| !entry.live)
+        .filter_map(|(key, entry)| Some((key, name_on_board(key, &board)?, entry)))
+        .take(END_SCREEN_BEST_SHOWN);
+
+    for (rank, (key, name, entry)) in best_runs.enumerate() {
+        let text = format!("{}. {}  {:.2}s  {}", rank + 1, name, entry.time, entry.date);
+        let dim = measure_text(&text, None, font_size as u16, 1.0);
+        let color = if game_info.client.key() == Some(key) { YELLOW } else { WHITE };
+
+        y += dim.height + padding;
+        draw_text(&text, x, y, font_size, color);
     }
-}   
+}
+
+fn show_menu_error(game_info: &mut Game, error: &'static str) {
+    game_info.menu_error = Some(error);
+    game_info.menu_error_timer = Timer::new(&game_info.clock);
+}
+
+fn parse_seed(text: &str) -> Result<Option<u64>, &'static str> {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    text.parse::<u64>()
+        .map(Some)
+        .map_err(|_| "Seed must be a whole number")
+}
 
 async fn menu_state(game_info: &mut Game) {
     clear_background(BLACK);
@@ -426,82 +1279,209 @@ async fn menu_state(game_info: &mut Game) {
 
     draw_text(text, x, y, font_size, WHITE);
 
+    poll_pending_run(game_info);
+
+    if game_info.game_state != GameState::Menu {
+        return;
+    }
+
+    // the menu waits for the server's answer, at most a few seconds before the join counts as offline
+    if game_info.pending_run.is_some() {
+        let text = format!("Joining as {}...", game_info.resources.string_buffer);
+        let text_dimensions = measure_text(&text, None, font_size as u16, 1.0);
+
+        draw_text(&text, screen_width() / 2.0 - text_dimensions.width / 2.0, screen_height() / 2.0, font_size, WHITE);
+
+        return;
+    }
+
     let text = "Play";
 
     let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
-    let text_width = text_dimensions.width;
+    let play_size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
 
-    let x = screen_width() / 2.0 - text_width * 3.0 / 2.0;
+    let x = screen_width() / 2.0 - play_size.x / 2.0;
     let y = 128.0;
 
     if widgets::Button::new("Play")
         .position(Vec2::new(x, y))
-        .size(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0)
-        .ui(&mut *root_ui())
+        .size(play_size)
+        .ui(&mut root_ui())
         {
-            if game_info.resources.string_buffer.len() == 0 {
-                game_info.show_name_already_exists = true;
-                game_info.show_name_timer = Timer::new();
-
-                return;
+            let requested_seed = match parse_seed(&game_info.resources.seed_buffer) {
+                Ok(seed) => seed,
+                Err(error) => {
+                    show_menu_error(game_info, error);
+                    return;
+                }
+            };
+
+            let name = game_info.resources.string_buffer.clone();
+            let board = Board::Standard(game_info.settings.difficulty);
+
+            match game_info.client.join(&name, &board) {
+                Ok(()) => game_info.pending_run = Some(PendingRun::Standard(requested_seed)),
+                Err(error) => show_menu_error(game_info, error.message())
             }
 
-            let client = Client::new(game_info.resources.string_buffer.clone());
+            return;
+        }
 
-            if client.is_err() {
-                game_info.show_name_already_exists = true;
-                game_info.show_name_timer = Timer::new();
-                return;
-            }
+    // beside Play in landscape, on their own row below it when the screen is too narrow
+    let portrait = screen_width() < screen_height();
+    let row_offset = if portrait { play_size.y + 16.0 } else { 0.0 };
+
+    let play_left = x;
+    let play_right = x + play_size.x;
 
+    let text = "Settings";
 
-            game_info.game_state = GameState::Playing;
-            *game_info = create_game(game_info.resources.clone(), GameState::Playing, client.unwrap()).await;
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let button_size = fit_widget(Vec2::new(text_dimensions.width * 1.5, text_dimensions.height * 3.0));
+
+    let x = if portrait { screen_width() / 2.0 - 8.0 - button_size.x } else { play_left - 32.0 - button_size.x };
 
-            play_sound(&game_info.resources.start_audio, PlaySoundParams { looped: false, volume: SOUND_EFFECT_VOLUME_RATIO });
-            play_sound(&game_info.resources.soundtrack, PlaySoundParams { looped: true, volume: SOUNDTRACK_VOLUME_RATIO });
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y + row_offset))
+        .size(button_size)
+        .ui(&mut root_ui())
+        {
+            open_settings(game_info);
+            return;
+        }
+
+    let text = "Load Replay";
+
+    let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+    let button_size = fit_widget(Vec2::new(text_dimensions.width * 1.5, text_dimensions.height * 3.0));
+
+    let x = if portrait { screen_width() / 2.0 + 8.0 } else { play_right + 32.0 };
+    let y = 128.0 + row_offset;
+
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y))
+        .size(button_size)
+        .ui(&mut root_ui())
+        {
+            match storage::read(REPLAY_FILE).map(|bytes| Replay::from_bytes(&bytes)) {
+                Some(Ok(replay)) => match start_replay(game_info, replay, GameState::Menu) {
+                    Ok(()) => return,
+                    Err(_) => show_menu_error(game_info, "Saved replay was recorded with different game tuning")
+                },
+                Some(Err(_)) => show_menu_error(game_info, "Saved replay is corrupt or from another version"),
+                None => show_menu_error(game_info, "No saved replay found")
+            }
         }
 
     let text = "   Player Name   ";
 
     let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
-    let text_width = text_dimensions.width;
+    let size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
 
-    let x = screen_width() / 2.0 - text_width * 3.0 / 2.0;
-    let y = 250.0;
+    let x = screen_width() / 2.0 - size.x / 2.0;
+    let y = 224.0 + row_offset;
 
     widgets::InputText::new(hash!())
         .position(Vec2::new(x, y))
-        .size(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0)
-        .ui(&mut *root_ui(), &mut game_info.resources.string_buffer); 
+        .size(size)
+        .ui(&mut root_ui(), &mut game_info.resources.string_buffer); 
 
 
-    let text = "Name already exists or you haven't entered a name";
+    let text = "   Player Name   ";
 
     let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
-    let text_width = text_dimensions.width;
+    let size = fit_widget(Vec2::new(text_dimensions.width, text_dimensions.height) * 3.0);
 
-    let x = screen_width() / 2.0 - text_width / 2.0;
-    let y = 350.0;
+    let x = screen_width() / 2.0 - size.x / 2.0;
+    let y = 320.0 + row_offset;
+
+    widgets::InputText::new(hash!())
+        .label("Seed (optional)")
+        .position(Vec2::new(x, y))
+        .size(size)
+        .ui(&mut root_ui(), &mut game_info.resources.seed_buffer); 
+
+    // sized for the longest name so the button doesn't jump around while cycling
+    let text_dimensions = measure_text("Difficulty: Insane", None, font_size as u16, 1.0);
+    let size = fit_widget(Vec2::new(text_dimensions.width * 1.5, text_dimensions.height * 3.0));
+
+    let x = if portrait { screen_width() / 2.0 - size.x / 2.0 } else { screen_width() / 2.0 - 8.0 - size.x };
+    let y = 416.0 + row_offset;
+
+    if widgets::Button::new(format!("Difficulty: {}", game_info.settings.difficulty.name()))
+        .position(Vec2::new(x, y))
+        .size(size)
+        .ui(&mut root_ui())
+        {
+            game_info.settings.difficulty = game_info.settings.difficulty.next();
+            game_info.ghost = Ghost::load(game_info.settings.difficulty);
+
+            if let Err(error) = game_info.settings.save() {
+                error!("failed to save settings: {error}");
+            }
+        }
+
+    // the daily challenge sits beside the difficulty since it ignores it, stacked below in portrait
+    let attempt_used = *game_info.daily_attempt_used.get_or_insert_with(|| DailyChallenge::today().official_attempt_used());
+    let text = if attempt_used { "Daily Practice" } else { "Daily Challenge" };
+
+    let x = if portrait { screen_width() / 2.0 - size.x / 2.0 } else { screen_width() / 2.0 + 8.0 };
+    let y = if portrait { y + size.y + 16.0 } else { y };
+    let row_offset = if portrait { row_offset + size.y + 16.0 } else { row_offset };
+
+    if widgets::Button::new(text)
+        .position(Vec2::new(x, y))
+        .size(size)
+        .ui(&mut root_ui())
+        {
+            start_daily_challenge(game_info);
+            return;
+        }
+
+
+    if let Some(text) = game_info.menu_error {
+        let text_dimensions = measure_text(text, None, font_size as u16, 1.0);
+        let text_width = text_dimensions.width;
+
+        let x = screen_width() / 2.0 - text_width / 2.0;
+        let y = 516.0 + row_offset;
 
-    if game_info.show_name_already_exists {
         draw_text(text, x, y, 32.0, WHITE);
 
-        if game_info.show_name_timer.has_elapsed(Duration::from_secs(3)) {
-            game_info.show_name_already_exists = false;
+        if game_info.menu_error_timer.has_elapsed(&game_info.clock, Duration::from_secs(3)) {
+            game_info.menu_error = None;
         }
     }
 
     
-    let text = "Space/left click/tap to Jump\nMove mouse to direct where jump will go\nGreen guys good red guys bad\nLast as long as possible.";
+    let touch = game_info.input_reader.touch_controls().is_some();
+
+    let jump_line = if touch {
+        "Hold the right side to Jump".to_owned()
+    } else {
+        format!("{} to Jump", game_info.settings.bindings.describe(Action::Jump))
+    };
+    let aim_line = match game_info.settings.aim_mode {
+        _ if touch => "Drag on the left side to aim",
+        AimMode::Mouse => "Move mouse to direct where jump will go",
+        AimMode::Keyboard => "Steer the arrow with the aim keys to direct the jump"
+    };
+    let text = format!("{jump_line}\n{aim_line}\nGreen guys good red guys bad\nLast as long as possible.");
+
+    let x = (screen_width() / 2.0 - measure_text(&jump_line, None, font_size as u16, 1.0).width / 2.0).max(SCREEN_MARGIN);
+    let y = 572.0 + row_offset;
+
+    draw_multiline_text(&text, x, y, font_size, None, WHITE);
+}
 
-    let x = screen_width() / 2.0 - measure_text("Space/left click/tap to Jump", None, font_size as u16, 1.0).width / 2.0;
-    let y = 512.0;
 
-    draw_multiline_text(text, x, y, font_size, None, WHITE);
+// keeps a widget inside narrow portrait screens and at least a fingertip tall
+fn fit_widget(size: Vec2) -> Vec2 {
+    Vec2::new(
+        size.x.min(screen_width() - SCREEN_MARGIN * 2.0), 
+        size.y.max(MIN_TOUCH_TARGET))
 }
 
-
 pub fn draw_rectangle_screen(position: Vec2, size: Vec2, color: Color) {
     let position = pixel_space(position);
     let size = pixel_space(size);
@@ -531,3 +1511,23 @@ pub fn draw_texture_screen(texture: &Texture2D, position: Vec2, size: Vec2, colo
     );
 }
 
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn restarting_from_the_pause_overlay_runs_the_clock() {
+        let mut clock = GameClock::new();
+
+        clock.pause();
+        restart_clock(&mut clock);
+
+        let timer = Timer::new(&clock);
+
+        assert_eq!(clock.advance(1.0), 1.0);
+        assert_eq!(timer.elapsed(&clock), Duration::from_secs(1));
+    }
+}
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use macroquad::prelude::*;
 
-use crate::{game::{sprite::Sprite, RANDOM}, timer::Timer};
+use crate::{simulation::Player, timer::{GameClock, Timer}};
 
 
 #[derive(Clone)]
@@ -11,11 +11,12 @@ pub struct BackgroundPass {
     descent: f64, 
     time_elapsed: f64, 
     stage_change_timer: Timer,
+    stage_interval: Duration,
     stage: i32
 }
 
 impl BackgroundPass {
-    pub async fn new() -> BackgroundPass {
+    pub async fn new(clock: &GameClock, stage_interval: f64) -> BackgroundPass {
         let fragment_shader_source = load_string("assets/shaders/fragment.glsl").await;
         let vertex_shader_source = load_string("assets/shaders/vertex.glsl").await;
 
@@ -55,31 +56,33 @@ impl BackgroundPass {
             material: bg_material.expect("failed to load material"), 
             descent: 0.0, 
             time_elapsed: 0.0,
-            stage_change_timer: Timer::new(),
+            stage_change_timer: Timer::new(clock),
+            stage_interval: Duration::from_secs_f64(stage_interval),
             stage: 0
         }
     }
 
 
-    pub fn render(&mut self, delta_time: f64, start_time: f64, player: &Sprite) {
+    // delta_time comes from the game clock, so the animation and stages freeze with it
+    pub fn render(&mut self, clock: &GameClock, delta_time: f64, player: &Player, alpha: f32) {
         clear_background(WHITE);
 
         gl_use_material(&self.material);
 
 
-        if self.stage_change_timer.has_elapsed(Duration::from_secs(20)) {
+        if self.stage_change_timer.has_elapsed(clock, self.stage_interval) {
             self.stage = (self.stage + 1) % 4;
             println!("{}", self.stage);
-            self.stage_change_timer.reset();
+            self.stage_change_timer.reset(clock);
         }
 
         self.material.set_uniform("u_ScreenSize", (screen_width(), screen_height()));
-        self.material.set_uniform("u_Time", (get_time() - start_time) as f32);
+        self.material.set_uniform("u_Time", self.time_elapsed as f32);
         self.material.set_uniform("u_BouncesLeft", player.boost_counter as f32);
         self.material.set_uniform("u_Descent", self.descent as f32);
-        self.material.set_uniform("u_PlayerPosition", player.get_bounds().get_center());
+        self.material.set_uniform("u_PlayerPosition", player.get_interpolated_bounds(alpha).get_center());
         self.material.set_uniform("u_PlayerRadius", player.view_radius);
-        self.material.set_uniform("u_Random", RANDOM.gen_range(0.01, 0.03) as f32);
+        self.material.set_uniform("u_Random", rand::gen_range(0.01, 0.03) as f32);
         self.material.set_uniform("u_Stage", self.stage as f32);
 
         draw_rectangle(0.0, 0.0, screen_width(), screen_height(), WHITE);
@@ -92,10 +95,14 @@ impl BackgroundPass {
         self.descent += delta_time * multiplier;
     }
 
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, clock: &GameClock) {
         self.descent = 0.0;
         self.time_elapsed = 0.0;
-        self.stage_change_timer.reset();
+        self.stage_change_timer.reset(clock);
         self.stage = 0;
     }
+
+    pub fn set_stage_interval(&mut self, stage_interval: f64) {
+        self.stage_interval = Duration::from_secs_f64(stage_interval);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+use macroquad::prelude::*;
+
+use crate::simulation::GameConfig;
+
+pub const GAME_CONFIG_FILE: &str = "assets/game_config.toml";
+
+// falls back to the built in tuning when the file is missing or invalid, the error says which value is wrong
+pub async fn load_game_config() -> GameConfig {
+    match load_string(GAME_CONFIG_FILE).await {
+        Ok(text) => GameConfig::from_toml(&text).unwrap_or_else(|error| {
+            error!("{GAME_CONFIG_FILE}: {error}");
+            GameConfig::default()
+        }),
+        Err(error) => {
+            error!("failed to load {GAME_CONFIG_FILE}: {error}");
+            GameConfig::default()
+        }
+    }
+}
+
+// Desktop debug builds look at the file's modification time about once a second and hand out the new
+// config when it changed and is valid. Other builds never reload.
+#[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+pub struct ConfigWatcher {
+    modified: Option<std::time::SystemTime>,
+    last_check: f64
+}
+
+#[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
+impl ConfigWatcher {
+    const CHECK_INTERVAL: f64 = 1.0;
+
+    pub fn new() -> ConfigWatcher {
+        ConfigWatcher {
+            modified: ConfigWatcher::modified(),
+            last_check: get_time()
+        }
+    }
+
+    fn modified() -> Option<std::time::SystemTime> {
+        std::fs::metadata(GAME_CONFIG_FILE).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    pub fn poll(&mut self) -> Option<GameConfig> {
+        if get_time() - self.last_check < ConfigWatcher::CHECK_INTERVAL {
+            return None;
+        }
+
+        self.last_check = get_time();
+
+        let modified = ConfigWatcher::modified();
+
+        if modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+
+        let text = std::fs::read_to_string(GAME_CONFIG_FILE)
+            .map_err(|error| error!("failed to reload {GAME_CONFIG_FILE}: {error}"))
+            .ok()?;
+
+        match GameConfig::from_toml(&text) {
+            Ok(config) => {
+                info!("reloaded {GAME_CONFIG_FILE}");
+                Some(config)
+            },
+            Err(error) => {
+                error!("{GAME_CONFIG_FILE}: {error}, keeping the previous config");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(all(debug_assertions, not(target_arch = "wasm32"))))]
+pub struct ConfigWatcher;
+
+#[cfg(not(all(debug_assertions, not(target_arch = "wasm32"))))]
+impl ConfigWatcher {
+    pub fn new() -> ConfigWatcher {
+        ConfigWatcher
+    }
+
+    pub fn poll(&mut self) -> Option<GameConfig> {
+        None
+    }
+}
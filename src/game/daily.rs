@@ -0,0 +1,45 @@
+use crate::calendar::*;
+use crate::storage;
+
+// Holds the day number of the last official attempt. Only this file enforces the one attempt rule, so deleting it
+// (or clearing the page's storage on the web) gives another official attempt; the server doesn't check.
+const DAILY_FILE: &str = "daily.txt";
+
+// The challenge for one UTC calendar day. Everyone derives the same seed from the day number, so there is
+// nothing to fetch from a server.
+pub struct DailyChallenge {
+    day: u64
+}
+
+impl DailyChallenge {
+    pub fn today() -> DailyChallenge {
+        DailyChallenge {
+            day: current_utc_day()
+        }
+    }
+
+    // splitmix64, so neighbouring days don't get similar seeds
+    pub fn seed(&self) -> u64 {
+        let mut z = self.day.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+        z ^ (z >> 31)
+    }
+
+    pub fn date(&self) -> String {
+        utc_date(self.day)
+    }
+
+    pub fn official_attempt_used(&self) -> bool {
+        storage::read(DAILY_FILE)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|text| text.trim().parse::<u64>().ok())
+            .is_some_and(|day| day == self.day)
+    }
+
+    // recorded when the run starts, so quitting a bad attempt doesn't give another one
+    pub fn use_official_attempt(&self) -> Result<(), String> {
+        storage::write(DAILY_FILE, self.day.to_string().as_bytes())
+    }
+}
@@ -0,0 +1,91 @@
+use macroquad::math::*;
+
+use crate::simulation::Difficulty;
+use crate::storage;
+
+// Ghost file layout, all values little endian:
+//   magic "SJGH", version u16, best time f64, position count u32, then x f32, y f32 per tick
+const GHOST_MAGIC: &[u8; 4] = b"SJGH";
+const GHOST_VERSION: u16 = 1;
+
+// one personal best per difficulty
+fn ghost_file(difficulty: Difficulty) -> String {
+    format!("ghost_{}.sjg", difficulty.name().to_lowercase())
+}
+
+const HEADER_SIZE: usize = 4 + 2 + 8 + 4;
+
+// player positions of the personal best run, one per simulation tick while alive
+pub struct Ghost {
+    best_time: f64,
+    positions: Vec<Vec2>
+}
+
+impl Ghost {
+    pub fn new(best_time: f64, positions: Vec<Vec2>) -> Ghost {
+        Ghost { 
+            best_time, 
+            positions 
+        }
+    }
+
+    pub fn load(difficulty: Difficulty) -> Option<Ghost> {
+        storage::read(&ghost_file(difficulty)).and_then(|bytes| Ghost::from_bytes(&bytes))
+    }
+
+    pub fn save(&self, difficulty: Difficulty) -> Result<(), String> {
+        storage::write(&ghost_file(difficulty), &self.to_bytes())
+    }
+
+    pub fn best_time(&self) -> f64 {
+        self.best_time
+    }
+
+    pub fn position(&self, tick: usize, alpha: f32) -> Option<Vec2> {
+        let current = *self.positions.get(tick)?;
+        let previous = tick.checked_sub(1)
+            .and_then(|tick| self.positions.get(tick))
+            .copied()
+            .unwrap_or(current);
+
+        Some(previous.lerp(current, alpha))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.positions.len() * 8);
+
+        bytes.extend_from_slice(GHOST_MAGIC);
+        bytes.extend_from_slice(&GHOST_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.best_time.to_le_bytes());
+        bytes.extend_from_slice(&(self.positions.len() as u32).to_le_bytes());
+
+        for position in &self.positions {
+            bytes.extend_from_slice(&position.x.to_le_bytes());
+            bytes.extend_from_slice(&position.y.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Ghost> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != GHOST_MAGIC || u16::from_le_bytes([bytes[4], bytes[5]]) != GHOST_VERSION {
+            return None;
+        }
+
+        let best_time = f64::from_le_bytes(bytes[6..14].try_into().ok()?);
+        let count = u32::from_le_bytes(bytes[14..18].try_into().ok()?) as usize;
+
+        let positions: Vec<Vec2> = bytes[HEADER_SIZE..]
+            .chunks_exact(8)
+            .map(|chunk| Vec2::new(
+                f32::from_le_bytes(chunk[0..4].try_into().unwrap()), 
+                f32::from_le_bytes(chunk[4..8].try_into().unwrap())))
+            .collect();
+
+        if positions.len() != count {
+            return None;
+        }
+
+        Some(Ghost::new(best_time, positions))
+    }
+}
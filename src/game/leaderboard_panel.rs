@@ -0,0 +1,161 @@
+use macroquad::prelude::*;
+
+use crate::client::ConnectionStatus;
+
+pub const LEADERBOARD_ROWS: usize = 10;
+
+const FONT_SIZE: u16 = 32;
+const STATUS_FONT_SIZE: u16 = 24;
+const LIVE_MARKER: &str = "LIVE";
+const PADDING: f32 = 10.0;
+const SCORE_COLUMN_WIDTH: f32 = 100.0;
+
+// what the panel shows, the layout is kept until any of it changes
+#[derive(PartialEq)]
+pub struct PanelSource {
+    pub board: String,
+    pub status: Option<ConnectionStatus>,
+    // layout version of the board the rows came from, see Standings::layout_version
+    pub revision: u64
+}
+
+#[derive(Default)]
+struct Label {
+    text: String,
+    dim: TextDimensions
+}
+
+impl Label {
+    fn new(text: String, font_size: u16) -> Label {
+        let dim = measure_text(&text, None, font_size, 1.0);
+
+        Label { text, dim }
+    }
+}
+
+struct Row {
+    name: Label,
+    time: f64,
+    score: Label,
+    // the run is in progress
+    live: bool
+}
+
+impl Row {
+    fn set_time(&mut self, time: f64) {
+        self.time = time;
+        self.score = Label::new(format!("{time:.2}s"), FONT_SIZE);
+    }
+}
+
+// The leaderboard in the top right corner. Text is formatted and measured when the board changes, not every frame,
+// and while rows only tick up in place just their times are formatted again.
+#[derive(Default)]
+pub struct LeaderboardPanel {
+    source: Option<PanelSource>,
+    // version of the board the times were taken from
+    version: u64,
+    title: Label,
+    status: Option<(Label, Color)>,
+    // best first
+    rows: Vec<Row>,
+    live_marker: Label,
+    // shown instead of the rows while there are none
+    placeholder: Label,
+    max_name_width: f32
+}
+
+impl LeaderboardPanel {
+    pub fn is_laid_out_for(&self, source: &PanelSource) -> bool {
+        self.source.as_ref() == Some(source)
+    }
+
+    // rows are (name, time, live)
+    pub fn lay_out<'a>(&mut self, source: PanelSource, version: u64, rows: impl Iterator<Item = (&'a str, f64, bool)>) {
+        // backends without a server only hold the runs played on this machine
+        let title = if source.status.is_none() { "Local best" } else { "Leaderboard" };
+
+        self.title = Label::new(format!("{title} ({})       ", source.board), FONT_SIZE);
+
+        self.status = source.status.map(|status| {
+            let color = match status {
+                ConnectionStatus::Online => GREEN,
+                ConnectionStatus::Connecting | ConnectionStatus::Reconnecting => YELLOW,
+                ConnectionStatus::Offline => RED
+            };
+
+            (Label::new(status.label().to_owned(), STATUS_FONT_SIZE), color)
+        });
+
+        self.rows = rows.take(LEADERBOARD_ROWS)
+            .map(|(name, time, live)| {
+                let mut row = Row { name: Label::new(name.to_owned(), FONT_SIZE), time, score: Label::default(), live };
+                row.set_time(time);
+                row
+            })
+            .collect();
+
+        self.max_name_width = self.rows.iter()
+            .map(|row| row.name.dim.width)
+            .fold(0.0, f32::max);
+
+        self.live_marker = Label::new(LIVE_MARKER.to_owned(), STATUS_FONT_SIZE);
+
+        self.placeholder = Label::new("no runs yet".to_owned(), FONT_SIZE);
+
+        self.source = Some(source);
+        self.version = version;
+    }
+
+    // the same rows as the last `lay_out` with newer times, only the times that changed are formatted again
+    pub fn update_times(&mut self, version: u64, rows: impl Iterator<Item = (f64, bool)>) {
+        if self.version == version {
+            return;
+        }
+
+        for (row, (time, live)) in self.rows.iter_mut().zip(rows) {
+            if row.time != time {
+                row.set_time(time);
+            }
+
+            row.live = live;
+        }
+
+        self.version = version;
+    }
+
+    pub fn draw(&self) {
+        draw_text(&self.title.text, screen_width() - self.title.dim.width, self.title.dim.height, FONT_SIZE as f32, WHITE);
+
+        let mut curr_y = self.title.dim.height;
+
+        if let Some((status, color)) = &self.status {
+            curr_y += status.dim.height + PADDING;
+            draw_text(&status.text, screen_width() - status.dim.width - PADDING, curr_y, STATUS_FONT_SIZE as f32, *color);
+        }
+
+        if self.rows.is_empty() {
+            let placeholder = &self.placeholder;
+
+            draw_text(&placeholder.text, screen_width() - placeholder.dim.width - PADDING, curr_y + placeholder.dim.height + PADDING, FONT_SIZE as f32, WHITE);
+            return;
+        }
+
+        let score_x = screen_width() - PADDING;
+        let name_x = score_x - SCORE_COLUMN_WIDTH - self.max_name_width;
+
+        for Row { name, score, live, .. } in &self.rows {
+            curr_y += name.dim.height + PADDING;
+
+            let x = name_x + (self.max_name_width - name.dim.width);
+
+            // players still in their run are marked in front of their name
+            if *live {
+                draw_text(&self.live_marker.text, x - self.live_marker.dim.width - PADDING, curr_y, STATUS_FONT_SIZE as f32, RED);
+            }
+
+            draw_text(&name.text, x, curr_y, FONT_SIZE as f32, WHITE);
+            draw_text(&score.text, score_x - score.dim.width, curr_y, FONT_SIZE as f32, WHITE);
+        }
+    }
+}
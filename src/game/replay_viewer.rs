@@ -0,0 +1,103 @@
+use macroquad::math::*;
+
+use crate::simulation::*;
+
+pub const REPLAY_SPEEDS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+// playback speed, pausing and single stepping all come from the game clock feeding advance
+pub struct ReplayViewer {
+    replay: Replay,
+    world: World,
+    config: GameConfig,
+    tick: usize,
+    accumulator: f64
+}
+
+impl ReplayViewer {
+    // the config has to match the one the run was recorded with or the replay drifts off course
+    pub fn new(replay: Replay, config: &GameConfig) -> Result<ReplayViewer, String> {
+        if replay.config_fingerprint() != config.fingerprint() {
+            return Err("Replay was recorded with different game tuning".to_owned());
+        }
+
+        Ok(ReplayViewer {
+            world: World::new(replay.seed(), config),
+            config: config.clone(),
+            replay,
+            tick: 0,
+            accumulator: 0.0
+        })
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn tick_count(&self) -> usize {
+        self.replay.frames().len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.tick_count()
+    }
+
+    // aim of the frame that produced the current world state
+    pub fn current_aim(&self) -> Vec2 {
+        self.tick.checked_sub(1)
+            .and_then(|tick| self.replay.frames().get(tick))
+            .map(|frame| frame.aim)
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / SIMULATION_TIMESTEP) as f32
+    }
+
+    pub fn advance(&mut self, delta_time: f64) -> Vec<WorldEvent> {
+        let mut events = Vec::new();
+
+        if self.is_finished() {
+            return events;
+        }
+
+        self.accumulator += delta_time;
+
+        while self.accumulator >= SIMULATION_TIMESTEP && !self.is_finished() {
+            events.extend_from_slice(self.step());
+            self.accumulator -= SIMULATION_TIMESTEP;
+        }
+
+        if self.is_finished() {
+            self.accumulator = 0.0;
+        }
+
+        events
+    }
+
+    // the simulation only runs forwards, so seeking backwards replays the run from its seed
+    pub fn seek(&mut self, tick: usize) {
+        let tick = tick.min(self.tick_count());
+
+        if tick < self.tick {
+            self.world = World::new(self.replay.seed(), &self.config);
+            self.tick = 0;
+        }
+
+        while self.tick < tick {
+            self.step();
+        }
+
+        self.accumulator = 0.0;
+    }
+
+    fn step(&mut self) -> &[WorldEvent] {
+        let frame = self.replay.frames()[self.tick];
+        self.tick += 1;
+
+        self.world.step(&frame)
+    }
+}
@@ -1,34 +1,18 @@
-
-use std::time::Duration;
-
 use macroquad::audio::*;
 use macroquad::math::*;
-use macroquad::miniquad::window::screen_size;
 use macroquad::prelude::*;
 
 use macroquad::texture::Texture2D;
 
 use crate::game::draw_texture_screen;
-use crate::timer::*;
 use crate::math::*;
+use crate::simulation::{Player, WorldEvent};
 
 pub struct Sprite {
-    pub boost_counter: i32,
-    pub view_radius: f32,
-
-    position: Vec2, 
-    size: Vec2,
-
-    velocity: Vec2,
-
     body: Texture2D,
     eye: Texture2D,
 
-    boost_timer: Timer,
-    boost_cooldown: Duration,
-    boost_speed_increase: f32,
     boost_sound: Sound, 
-    
     boing_sound: Sound
 }
 
@@ -50,121 +34,59 @@ impl Sprite {
         }
     }
 
-    pub async fn new(name: String, position: Vec2, size: Vec2, starting_view_radius: f32) -> Self {
+    pub async fn new(name: String) -> Self {
         Sprite {
-            boost_counter: 10,
-            view_radius: starting_view_radius,
-            position,
-            size, 
-
-            velocity: Vec2::new(0.0, 0.0),
-
             body: Sprite::load_sprite_texture(name.as_str(), "body").await, 
             eye: Sprite::load_sprite_texture(name.as_str(),  "eye").await, 
 
-            boost_timer: Timer::new(),
-            boost_cooldown: Duration::from_millis(500),
-            boost_speed_increase: 1.0,
             boost_sound: load_sound("assets/jump.wav").await.unwrap(), 
             boing_sound: load_sound("assets/boing.wav").await.unwrap()
         }
     }
 
-    pub fn get_bounds(&self) -> Bounds2D {
-        Bounds2D::new(self.position, self.size)
-    }
-
-    fn handle_movement(&mut self) {
-        let center = self.position + self.size / 2.0;
-
-        let mut direction = Vec2::from(mouse_position()) / Vec2::from(screen_size());
-        direction = direction - center;
-        direction = direction.normalize_or(Vec2::new(0.0, 0.0));
-
-        if direction.length() == 0.0 {
-            return;
+    // volume is the effects volume from the settings, scaled by the sprite's own mix level
+    pub fn handle_event(&self, event: &WorldEvent, volume: f32) {
+        match event {
+            WorldEvent::Jumped => {
+                play_sound(&self.boost_sound, PlaySoundParams { looped: false, volume: SPRITE_SOUND_EFFECT_VOLUME_RATIO * volume });
+            },
+            WorldEvent::BorderBounce => {
+                stop_sound(&self.boing_sound);
+                play_sound(&self.boing_sound, PlaySoundParams { looped: false, volume: SPRITE_SOUND_EFFECT_VOLUME_RATIO * volume });
+            },
+            _ => {}
         }
-
-        let mut scalar = 0.0;
-
-        if (is_key_down(KeyCode::Space) || is_mouse_button_pressed(MouseButton::Left)) && self.boost_timer.has_elapsed(self.boost_cooldown) && self.boost_counter > 0 {
-            scalar += self.boost_speed_increase;
-
-            self.boost_timer.reset();
-            self.boost_counter -= 1;
-
-            play_sound(&self.boost_sound, PlaySoundParams { looped: false, volume: SPRITE_SOUND_EFFECT_VOLUME_RATIO });
-        }   
-
-        self.velocity += direction * scalar;
-    }
-
-    fn handle_gravity(&mut self, delta_time: f64) {
-        const GRAVITY_CONSTANT: f32 = 1.1;
-        self.velocity += Vec2::new(0.0, GRAVITY_CONSTANT * delta_time as f32);
     }
 
-    fn draw_eye(&self, mut eye_center: Vec2, eye_size: Vec2, eye_origin: Vec2) {
-        let mouse_to_eye = Vec2::from(mouse_position()) / Vec2::from(screen_size()) - eye_center;
-        let distance = mouse_to_eye.length();
+    fn draw_eye(&self, mut eye_center: Vec2, eye_size: Vec2, eye_origin: Vec2, look_at: Vec2, color: Color) {
+        let target_to_eye = look_at - eye_center;
+        let distance = target_to_eye.length();
 
-        let eye_direction = mouse_to_eye.normalize_or(Vec2::new(1.0, 0.0)) * distance;
+        let eye_direction = target_to_eye.normalize_or(Vec2::new(1.0, 0.0)) * distance;
 
         eye_center = rotate_around(eye_direction, eye_center, eye_origin);
         let eye_position = eye_center - eye_size / 2.0;
 
-        draw_texture_screen(&self.eye, eye_position, eye_size, WHITE);
+        draw_texture_screen(&self.eye, eye_position, eye_size, color);
     }
 
-    pub fn draw(&self) {
-        draw_texture_screen(&self.body, self.position, self.size, WHITE);
-
-        let center = self.position + self.size / 2.0;
-
-        let eye_center = center - Vec2::new(-0.3, 0.1) * self.size; 
-        let eye_origin = center - Vec2::new(0.0, 0.1) * self.size;
-
-        let eye_size = self.size / 2.0;
-
-        self.draw_eye(eye_center, eye_size, eye_origin);
+    // look_at is the screen-space point the eye follows, usually the cursor
+    pub fn draw(&self, player: &Player, alpha: f32, look_at: Vec2) {
+        self.draw_at(player.get_interpolated_bounds(alpha), look_at, WHITE);
     }
 
-    fn handle_border(&mut self) {
-        let player_bounds = self.get_bounds();
+    pub fn draw_at(&self, bounds: Bounds2D, look_at: Vec2, color: Color) {
+        let size = bounds.get_size();
 
-        let mut force = Vec2::new(0.0, 0.0);
+        draw_texture_screen(&self.body, bounds.get_position(), size, color);
 
-        if player_bounds.get_position().x == 0.0 {
-            force += Vec2::new(1.0, 0.0);
-        } else if player_bounds.get_position().x == 1.0 - self.size.x {
-            force += Vec2::new(-1.0, 0.0);
-        }
-
-        if player_bounds.get_position().y == 0.0 {
-            force += Vec2::new(0.0, 1.0);
-        }
-
-        if force.length() > 0.0 {
-            stop_sound(&self.boing_sound);
-            play_sound(&self.boing_sound, PlaySoundParams { looped: false, volume: SPRITE_SOUND_EFFECT_VOLUME_RATIO });
-        }
-
-        force = force.normalize_or_zero() * self.velocity.length() * 1.1; 
-        self.velocity += force; //* delta_time as f32;
-    }
+        let center = bounds.get_center();
 
-    pub fn update(&mut self, delta_time: f64) {
-        self.handle_movement();
-        self.handle_gravity(delta_time);
-        self.handle_border();
+        let eye_center = center - Vec2::new(-0.3, 0.1) * size; 
+        let eye_origin = center - Vec2::new(0.0, 0.1) * size;
 
-        self.velocity = self.velocity.clamp_length(0.0, 40.0);
-        self.position += self.velocity * delta_time as f32;
-        self.velocity = self.velocity.lerp(Vec2::new(0.0, 0.0), 0.1 * delta_time as f32);
+        let eye_size = size / 2.0;
 
-        self.position = self.position.clamp(
-            Vec2::new(0.0, 0.0), 
-            Vec2::new(1.0 - self.size.x, f32::MAX),
-        );
+        self.draw_eye(eye_center, eye_size, eye_origin, look_at, color);
     }
-}
\ No newline at end of file
+}
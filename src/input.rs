@@ -0,0 +1,647 @@
+use macroquad::input::{is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, mouse_position, mouse_delta_position, get_last_key_pressed, KeyCode, MouseButton};
+use macroquad::math::*;
+use macroquad::miniquad::window::screen_size;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+mod gamepad;
+mod touch;
+
+pub use gamepad::*;
+pub use touch::*;
+
+// Maps physical keys and buttons onto game actions. Gameplay only ever sees the InputFrame built from
+// Controls, so tests and replays can feed the simulation without touching any device.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Jump,
+    Pause,
+    Restart,
+    AimUp,
+    AimDown,
+    AimLeft,
+    AimRight
+}
+
+pub const REBINDABLE_ACTIONS: [Action; 7] = [
+    Action::Jump, 
+    Action::Pause, 
+    Action::Restart, 
+    Action::AimUp, 
+    Action::AimDown, 
+    Action::AimLeft, 
+    Action::AimRight
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Jump => "Jump",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::AimUp => "Aim up",
+            Action::AimDown => "Aim down",
+            Action::AimLeft => "Aim left",
+            Action::AimRight => "Aim right"
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Pad(PadButton)
+}
+
+const MOUSE_BUTTONS: [(MouseButton, &str); 3] = [
+    (MouseButton::Left, "MouseLeft"),
+    (MouseButton::Right, "MouseRight"),
+    (MouseButton::Middle, "MouseMiddle")
+];
+
+// KeyCode has no serde support, so bindings are stored by name and only these keys can be bound
+const BINDABLE_KEYS: [(KeyCode, &str); 74] = [
+    (KeyCode::Space, "Space"), (KeyCode::Enter, "Enter"), (KeyCode::Escape, "Escape"), (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"), (KeyCode::LeftShift, "LeftShift"), (KeyCode::RightShift, "RightShift"),
+    (KeyCode::LeftControl, "LeftControl"), (KeyCode::RightControl, "RightControl"), (KeyCode::LeftAlt, "LeftAlt"),
+    (KeyCode::RightAlt, "RightAlt"), (KeyCode::Up, "Up"), (KeyCode::Down, "Down"), (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"), (KeyCode::A, "A"), (KeyCode::B, "B"), (KeyCode::C, "C"), (KeyCode::D, "D"),
+    (KeyCode::E, "E"), (KeyCode::F, "F"), (KeyCode::G, "G"), (KeyCode::H, "H"), (KeyCode::I, "I"),
+    (KeyCode::J, "J"), (KeyCode::K, "K"), (KeyCode::L, "L"), (KeyCode::M, "M"), (KeyCode::N, "N"),
+    (KeyCode::O, "O"), (KeyCode::P, "P"), (KeyCode::Q, "Q"), (KeyCode::R, "R"), (KeyCode::S, "S"),
+    (KeyCode::T, "T"), (KeyCode::U, "U"), (KeyCode::V, "V"), (KeyCode::W, "W"), (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"), (KeyCode::Z, "Z"), (KeyCode::Key0, "0"), (KeyCode::Key1, "1"), (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"), (KeyCode::Key4, "4"), (KeyCode::Key5, "5"), (KeyCode::Key6, "6"), (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"), (KeyCode::Key9, "9"), (KeyCode::F1, "F1"), (KeyCode::F2, "F2"), (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"), (KeyCode::F5, "F5"), (KeyCode::F6, "F6"), (KeyCode::F7, "F7"), (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"), (KeyCode::F10, "F10"), (KeyCode::F11, "F11"), (KeyCode::F12, "F12"),
+    (KeyCode::Comma, "Comma"), (KeyCode::Period, "Period"), (KeyCode::Slash, "Slash"), (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Apostrophe, "Apostrophe"), (KeyCode::Minus, "Minus"), (KeyCode::Equal, "Equal"),
+    (KeyCode::LeftBracket, "LeftBracket"), (KeyCode::RightBracket, "RightBracket"), (KeyCode::Backslash, "Backslash"),
+    (KeyCode::GraveAccent, "GraveAccent")
+];
+
+impl Binding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binding::Key(key) => BINDABLE_KEYS.iter()
+                .find(|(bindable, _)| bindable == key)
+                .map_or("Unknown", |(_, name)| name),
+            Binding::Mouse(button) => MOUSE_BUTTONS.iter()
+                .find(|(bindable, _)| bindable == button)
+                .map_or("Unknown", |(_, name)| name),
+            Binding::Pad(button) => PAD_BUTTONS.iter()
+                .find(|(bindable, _)| bindable == button)
+                .map_or("Unknown", |(_, name)| name)
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Binding> {
+        BINDABLE_KEYS.iter()
+            .find(|(_, key_name)| *key_name == name)
+            .map(|(key, _)| Binding::Key(*key))
+            .or_else(|| MOUSE_BUTTONS.iter()
+                .find(|(_, button_name)| *button_name == name)
+                .map(|(button, _)| Binding::Mouse(*button)))
+            .or_else(|| PAD_BUTTONS.iter()
+                .find(|(_, button_name)| *button_name == name)
+                .map(|(button, _)| Binding::Pad(*button)))
+    }
+
+    // the key or button pressed this frame, if it is one that can be bound
+    pub fn pressed_this_frame(pad: &PadInput) -> Option<Binding> {
+        let key = get_last_key_pressed()
+            .filter(|key| BINDABLE_KEYS.iter().any(|(bindable, _)| bindable == key))
+            .map(Binding::Key);
+
+        key.or_else(|| MOUSE_BUTTONS.iter()
+            .find(|(button, _)| is_mouse_button_pressed(*button))
+            .map(|(button, _)| Binding::Mouse(*button)))
+            .or_else(|| PAD_BUTTONS.iter()
+                .find(|(button, _)| pad.is_pressed(*button))
+                .map(|(button, _)| Binding::Pad(*button)))
+    }
+
+    pub fn same_device(&self, other: &Binding) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn is_down(&self, pad: &PadInput) -> bool {
+        match self {
+            Binding::Key(key) => is_key_down(*key),
+            Binding::Mouse(button) => is_mouse_button_down(*button),
+            Binding::Pad(button) => pad.current.is_down(*button)
+        }
+    }
+
+    pub fn is_pressed(&self, pad: &PadInput) -> bool {
+        match self {
+            Binding::Key(key) => is_key_pressed(*key),
+            Binding::Mouse(button) => is_mouse_button_pressed(*button),
+            Binding::Pad(button) => pad.is_pressed(*button)
+        }
+    }
+}
+
+// the pad as seen this frame and last frame, so bindings can tell presses from holds
+#[derive(Clone, Copy, Default)]
+pub struct PadInput {
+    current: PadState,
+    previous: PadState
+}
+
+impl PadInput {
+    pub fn is_pressed(&self, button: PadButton) -> bool {
+        self.current.is_pressed_since(&self.previous, button)
+    }
+}
+
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Binding, D::Error> {
+        let name = String::deserialize(deserializer)?;
+
+        Binding::from_name(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown binding \"{name}\"")))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub jump: Vec<Binding>,
+    pub pause: Vec<Binding>,
+    pub restart: Vec<Binding>,
+    pub aim_up: Vec<Binding>,
+    pub aim_down: Vec<Binding>,
+    pub aim_left: Vec<Binding>,
+    pub aim_right: Vec<Binding>
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings {
+            jump: vec![Binding::Key(KeyCode::Space), Binding::Mouse(MouseButton::Left), Binding::Pad(PadButton::South)],
+            pause: vec![Binding::Key(KeyCode::Escape), Binding::Key(KeyCode::P), Binding::Pad(PadButton::Start)],
+            restart: vec![Binding::Key(KeyCode::R), Binding::Pad(PadButton::Select)],
+            aim_up: vec![Binding::Key(KeyCode::Up), Binding::Key(KeyCode::W)],
+            aim_down: vec![Binding::Key(KeyCode::Down), Binding::Key(KeyCode::S)],
+            aim_left: vec![Binding::Key(KeyCode::Left), Binding::Key(KeyCode::A)],
+            aim_right: vec![Binding::Key(KeyCode::Right), Binding::Key(KeyCode::D)]
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        match action {
+            Action::Jump => &self.jump,
+            Action::Pause => &self.pause,
+            Action::Restart => &self.restart,
+            Action::AimUp => &self.aim_up,
+            Action::AimDown => &self.aim_down,
+            Action::AimLeft => &self.aim_left,
+            Action::AimRight => &self.aim_right
+        }
+    }
+
+    pub fn set(&mut self, action: Action, bindings: Vec<Binding>) {
+        match action {
+            Action::Jump => self.jump = bindings,
+            Action::Pause => self.pause = bindings,
+            Action::Restart => self.restart = bindings,
+            Action::AimUp => self.aim_up = bindings,
+            Action::AimDown => self.aim_down = bindings,
+            Action::AimLeft => self.aim_left = bindings,
+            Action::AimRight => self.aim_right = bindings
+        }
+    }
+
+    // Binds the key or button to the action in place of the action's bindings on the same device, so rebinding
+    // the keyboard keeps the mouse and pad bindings. The binding is taken away from any other action it was on.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        for other in REBINDABLE_ACTIONS {
+            let mut bindings = self.get(other).to_vec();
+            bindings.retain(|bound| *bound != binding);
+            self.set(other, bindings);
+        }
+
+        let mut bindings = self.get(action).to_vec();
+        let position = bindings.iter().position(|bound| bound.same_device(&binding)).unwrap_or(bindings.len());
+
+        bindings.retain(|bound| !bound.same_device(&binding));
+        bindings.insert(position, binding);
+
+        self.set(action, bindings);
+    }
+
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<&str> = self.get(action).iter().map(Binding::name).collect();
+
+        if names.is_empty() { "unbound".to_owned() } else { names.join(", ") }
+    }
+
+    pub fn is_pressed(&self, action: Action, pad: &PadInput) -> bool {
+        self.get(action).iter().any(|binding| binding.is_pressed(pad))
+    }
+
+    pub fn is_down(&self, action: Action, pad: &PadInput) -> bool {
+        self.get(action).iter().any(|binding| binding.is_down(pad))
+    }
+
+    // held keys and pad buttons keep jumping every time the cooldown allows, mouse buttons jump once per click
+    fn is_jump_triggered(&self, pad: &PadInput) -> bool {
+        self.jump.iter().any(|binding| match binding {
+            Binding::Mouse(_) => binding.is_pressed(pad),
+            _ => binding.is_down(pad)
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum AimMode {
+    #[default]
+    Mouse,
+    Keyboard
+}
+
+pub const AIM_MODE_NAMES: [&str; 2] = ["Mouse", "Keyboard"];
+
+impl AimMode {
+    pub fn index(&self) -> usize {
+        match self {
+            AimMode::Mouse => 0,
+            AimMode::Keyboard => 1
+        }
+    }
+
+    pub fn from_index(index: usize) -> AimMode {
+        match index {
+            1 => AimMode::Keyboard,
+            _ => AimMode::Mouse
+        }
+    }
+}
+
+// radians per second the keyboard aim turns towards the held direction
+const KEYBOARD_AIM_TURN_SPEED: f32 = 6.0;
+// how far in front of the player the eye looks when aiming with the keyboard or a pad
+const DIRECTION_LOOK_DISTANCE: f32 = 0.2;
+// stick deflection below this is treated as noise
+const STICK_DEAD_ZONE: f32 = 0.3;
+
+// everything the front end reads from the devices in one frame
+pub struct Controls {
+    pub aim: Vec2,
+    pub look_at: Vec2,
+    pub jump: bool,
+    pub pause: bool,
+    pub restart: bool
+}
+
+pub struct InputReader {
+    controller: Box<dyn ControllerSource>,
+    pad: PadInput,
+    // true from the moment the pad is touched until the mouse or keyboard is used again
+    pad_active: bool,
+    pad_direction: Vec2,
+    touch: TouchControls,
+    // true while fingers are the last thing used, taps then stop counting as mouse clicks
+    touch_active: bool,
+    // screen space angle, 0 points right and positive turns clockwise
+    keyboard_angle: f32
+}
+
+impl InputReader {
+    pub fn new() -> InputReader {
+        InputReader::with_controller(default_controller_source())
+    }
+
+    pub fn with_controller(controller: Box<dyn ControllerSource>) -> InputReader {
+        InputReader {
+            controller,
+            pad: PadInput::default(),
+            pad_active: false,
+            pad_direction: Vec2::new(0.0, -1.0),
+            touch: TouchControls::new(),
+            touch_active: false,
+            keyboard_angle: -std::f32::consts::FRAC_PI_2
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pad_direction = Vec2::new(0.0, -1.0);
+        self.touch.reset();
+        self.keyboard_angle = -std::f32::consts::FRAC_PI_2;
+    }
+
+    // call once per frame before reading any actions
+    pub fn poll_devices(&mut self) {
+        // fingers are tracked across frames, so touch updates even while the pad is in use
+        let touching = self.touch.update();
+
+        if self.poll_controller() {
+            return;
+        }
+
+        // macroquad turns taps into mouse events too, so the mouse only counts when no finger is down
+        let mouse_used = !touching && (mouse_delta_position() != Vec2::ZERO
+            || MOUSE_BUTTONS.iter().any(|(button, _)| is_mouse_button_pressed(*button)));
+
+        if touching {
+            self.touch_active = true;
+            self.pad_active = false;
+        } else if mouse_used || get_last_key_pressed().is_some() {
+            self.pad_active = false;
+            self.touch_active = false;
+        }
+    }
+
+    // reads the controller and makes it the active input once it's used, returns whether it was
+    fn poll_controller(&mut self) -> bool {
+        self.pad.previous = self.pad.current;
+        self.pad.current = self.controller.poll().unwrap_or_default();
+
+        let stick = self.pad.current.stick;
+        let pad_touched = stick.length() > STICK_DEAD_ZONE
+            || PAD_BUTTONS.iter().any(|(button, _)| self.pad.is_pressed(*button));
+
+        if stick.length() > STICK_DEAD_ZONE {
+            self.pad_direction = stick.normalize();
+        }
+
+        if pad_touched {
+            self.pad_active = true;
+            self.touch_active = false;
+        }
+
+        pad_touched
+    }
+
+    // the on screen controls to draw, None unless touch is the active input
+    pub fn touch_controls(&self) -> Option<&TouchControls> {
+        self.touch_active.then_some(&self.touch)
+    }
+
+    fn touch_direction(&self) -> Vec2 {
+        self.touch.direction().unwrap_or(Vec2::new(0.0, -1.0))
+    }
+
+    pub fn is_pressed(&self, bindings: &Bindings, action: Action) -> bool {
+        bindings.is_pressed(action, &self.pad)
+    }
+
+    // the action's keys and pad buttons only, for screens where mouse clicks work the on screen controls
+    pub fn is_pressed_ignoring_mouse(&self, bindings: &Bindings, action: Action) -> bool {
+        bindings.get(action).iter().any(|binding| !matches!(binding, Binding::Mouse(_)) && binding.is_pressed(&self.pad))
+    }
+
+    pub fn binding_pressed_this_frame(&self) -> Option<Binding> {
+        Binding::pressed_this_frame(&self.pad)
+    }
+
+    fn keyboard_direction(&self) -> Vec2 {
+        Vec2::from_angle(self.keyboard_angle)
+    }
+
+    // the aim as a fixed direction, None while the mouse cursor is the aim target
+    pub fn aim_direction(&self, aim_mode: AimMode) -> Option<Vec2> {
+        match aim_mode {
+            _ if self.pad_active => Some(self.pad_direction),
+            _ if self.touch_active => Some(self.touch_direction()),
+            AimMode::Keyboard => Some(self.keyboard_direction()),
+            AimMode::Mouse => None
+        }
+    }
+
+    pub fn read(&mut self, bindings: &Bindings, aim_mode: AimMode, player_center: Vec2, delta_time: f32) -> Controls {
+        let (aim, look_at) = match aim_mode {
+            _ if self.pad_active => (self.pad_direction, player_center + self.pad_direction * DIRECTION_LOOK_DISTANCE),
+            _ if self.touch_active => {
+                let direction = self.touch_direction();
+
+                (direction, player_center + direction * DIRECTION_LOOK_DISTANCE)
+            },
+            AimMode::Mouse => {
+                let cursor = Vec2::from(mouse_position()) / Vec2::from(screen_size());
+
+                (cursor - player_center, cursor)
+            },
+            AimMode::Keyboard => {
+                self.steer_keyboard_aim(bindings, delta_time);
+
+                let direction = self.keyboard_direction();
+
+                (direction, player_center + direction * DIRECTION_LOOK_DISTANCE)
+            }
+        };
+
+        // emulated clicks from taps would jump on every stick touch, so touch uses its own jump button
+        let jump = if self.touch_active { self.touch.is_jump_down() } else { bindings.is_jump_triggered(&self.pad) };
+
+        Controls {
+            aim,
+            look_at,
+            jump,
+            pause: bindings.is_pressed(Action::Pause, &self.pad) || self.touch.is_pause_pressed(),
+            restart: bindings.is_pressed(Action::Restart, &self.pad)
+        }
+    }
+
+    // turns the aim towards the direction of the held keys instead of snapping so small corrections are possible
+    fn steer_keyboard_aim(&mut self, bindings: &Bindings, delta_time: f32) {
+        let axis = |negative: Action, positive: Action| {
+            bindings.is_down(positive, &self.pad) as i32 as f32 - bindings.is_down(negative, &self.pad) as i32 as f32
+        };
+
+        let target = Vec2::new(axis(Action::AimLeft, Action::AimRight), axis(Action::AimUp, Action::AimDown));
+
+        if target == Vec2::ZERO {
+            return;
+        }
+
+        let difference = Vec2::from_angle(self.keyboard_angle).angle_between(target);
+        let max_turn = KEYBOARD_AIM_TURN_SPEED * delta_time;
+
+        self.keyboard_angle += difference.clamp(-max_turn, max_turn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    // hands the reader whatever state the test last set, None is an unplugged pad
+    struct VirtualController(Rc<Cell<Option<PadState>>>);
+
+    impl ControllerSource for VirtualController {
+        fn poll(&mut self) -> Option<PadState> {
+            self.0.get()
+        }
+    }
+
+    fn virtual_reader() -> (InputReader, Rc<Cell<Option<PadState>>>) {
+        let state = Rc::new(Cell::new(None));
+
+        (InputReader::with_controller(Box::new(VirtualController(state.clone()))), state)
+    }
+
+    // keyboard and mouse bindings read macroquad's window state, which tests don't have
+    fn pad_bindings() -> Bindings {
+        Bindings {
+            jump: vec![Binding::Pad(PadButton::South)],
+            pause: vec![Binding::Pad(PadButton::Start)],
+            restart: vec![Binding::Pad(PadButton::Select)],
+            aim_up: Vec::new(),
+            aim_down: Vec::new(),
+            aim_left: Vec::new(),
+            aim_right: Vec::new()
+        }
+    }
+
+    fn stick(x: f32, y: f32) -> Option<PadState> {
+        Some(PadState::new(Vec2::new(x, y), &[]))
+    }
+
+    #[test]
+    fn the_stick_aims_outside_its_dead_zone() {
+        let (mut reader, pad) = virtual_reader();
+
+        pad.set(stick(0.2, 0.1));
+
+        assert!(!reader.poll_controller());
+        assert_eq!(reader.aim_direction(AimMode::Mouse), None);
+
+        pad.set(stick(0.0, 0.9));
+
+        assert!(reader.poll_controller());
+        assert_eq!(reader.aim_direction(AimMode::Mouse), Some(Vec2::new(0.0, 1.0)));
+
+        // letting go keeps the last direction instead of snapping back to the dead zone's noise
+        pad.set(stick(0.1, 0.0));
+        reader.poll_controller();
+
+        assert_eq!(reader.aim_direction(AimMode::Keyboard), Some(Vec2::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn the_pad_aims_from_the_player() {
+        let (mut reader, pad) = virtual_reader();
+        let center = Vec2::new(0.5, 0.5);
+
+        pad.set(stick(-3.0, 4.0));
+        reader.poll_controller();
+
+        let controls = reader.read(&pad_bindings(), AimMode::Mouse, center, 1.0 / 60.0);
+
+        assert_eq!(controls.aim, Vec2::new(-0.6, 0.8));
+        assert_eq!(controls.look_at, center + Vec2::new(-0.6, 0.8) * DIRECTION_LOOK_DISTANCE);
+    }
+
+    #[test]
+    fn pad_buttons_trigger_their_actions() {
+        let (mut reader, pad) = virtual_reader();
+        let bindings = pad_bindings();
+
+        pad.set(Some(PadState::new(Vec2::ZERO, &[PadButton::South, PadButton::Start])));
+        reader.poll_controller();
+
+        let controls = reader.read(&bindings, AimMode::Mouse, Vec2::ZERO, 1.0 / 60.0);
+
+        assert!(reader.is_pressed(&bindings, Action::Jump));
+        assert!(controls.jump && controls.pause && !controls.restart);
+
+        // held buttons keep jumping but only press once
+        pad.set(Some(PadState::new(Vec2::ZERO, &[PadButton::South])));
+        reader.poll_controller();
+
+        let controls = reader.read(&bindings, AimMode::Mouse, Vec2::ZERO, 1.0 / 60.0);
+
+        assert!(!reader.is_pressed(&bindings, Action::Jump));
+        assert!(controls.jump && !controls.pause);
+
+        // unbound buttons do nothing, and an unplugged pad releases everything
+        pad.set(Some(PadState::new(Vec2::ZERO, &[PadButton::East])));
+        reader.poll_controller();
+
+        assert!(!reader.read(&bindings, AimMode::Mouse, Vec2::ZERO, 1.0 / 60.0).jump);
+
+        pad.set(None);
+        reader.poll_controller();
+
+        assert!(REBINDABLE_ACTIONS.iter().all(|action| !reader.is_pressed(&bindings, *action)));
+    }
+
+    #[test]
+    fn rebinding_keeps_the_other_devices() {
+        let mut bindings = Bindings::default();
+
+        bindings.rebind(Action::Jump, Binding::Key(KeyCode::J));
+
+        assert_eq!(bindings.jump, vec![Binding::Key(KeyCode::J), Binding::Mouse(MouseButton::Left), Binding::Pad(PadButton::South)]);
+
+        bindings.rebind(Action::Jump, Binding::Pad(PadButton::East));
+
+        assert_eq!(bindings.jump, vec![Binding::Key(KeyCode::J), Binding::Mouse(MouseButton::Left), Binding::Pad(PadButton::East)]);
+    }
+
+    #[test]
+    fn rebinding_replaces_every_binding_of_the_device() {
+        let mut bindings = Bindings::default();
+
+        bindings.rebind(Action::AimUp, Binding::Key(KeyCode::I));
+
+        assert_eq!(bindings.aim_up, vec![Binding::Key(KeyCode::I)]);
+
+        // a device the action had no binding for is added
+        bindings.rebind(Action::AimUp, Binding::Pad(PadButton::North));
+
+        assert_eq!(bindings.aim_up, vec![Binding::Key(KeyCode::I), Binding::Pad(PadButton::North)]);
+    }
+
+    #[test]
+    fn a_binding_moves_away_from_the_action_that_had_it() {
+        let mut bindings = Bindings::default();
+
+        bindings.rebind(Action::Jump, Binding::Key(KeyCode::P));
+
+        assert_eq!(bindings.pause, vec![Binding::Key(KeyCode::Escape), Binding::Pad(PadButton::Start)]);
+        assert!(bindings.jump.contains(&Binding::Key(KeyCode::P)));
+
+        // the last binding of an action can be taken too, the action is then unbound
+        bindings.rebind(Action::Jump, Binding::Key(KeyCode::R));
+
+        assert_eq!(bindings.describe(Action::Restart), "PadSelect");
+        assert!(!bindings.jump.contains(&Binding::Key(KeyCode::P)));
+    }
+
+    #[test]
+    fn bindings_survive_a_toml_round_trip() {
+        let mut bindings = Bindings::default();
+
+        bindings.rebind(Action::Jump, Binding::Mouse(MouseButton::Right));
+        bindings.rebind(Action::Restart, Binding::Key(KeyCode::F5));
+
+        let text = toml::to_string(&bindings).unwrap();
+
+        assert_eq!(toml::from_str::<Bindings>(&text).unwrap(), bindings);
+    }
+
+    #[test]
+    fn missing_actions_keep_their_defaults_and_unknown_names_are_errors() {
+        let bindings: Bindings = toml::from_str("jump = [\"J\", \"PadEast\"]").unwrap();
+
+        assert_eq!(bindings.jump, vec![Binding::Key(KeyCode::J), Binding::Pad(PadButton::East)]);
+        assert_eq!(bindings.pause, Bindings::default().pause);
+
+        assert!(toml::from_str::<Bindings>("jump = [\"Hyper\"]").is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use macroquad::math::Vec2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight
+}
+
+pub const PAD_BUTTONS: [(PadButton, &str); 14] = [
+    (PadButton::South, "PadSouth"),
+    (PadButton::East, "PadEast"),
+    (PadButton::West, "PadWest"),
+    (PadButton::North, "PadNorth"),
+    (PadButton::LeftBumper, "PadLeftBumper"),
+    (PadButton::RightBumper, "PadRightBumper"),
+    (PadButton::LeftTrigger, "PadLeftTrigger"),
+    (PadButton::RightTrigger, "PadRightTrigger"),
+    (PadButton::Select, "PadSelect"),
+    (PadButton::Start, "PadStart"),
+    (PadButton::DPadUp, "PadUp"),
+    (PadButton::DPadDown, "PadDown"),
+    (PadButton::DPadLeft, "PadLeft"),
+    (PadButton::DPadRight, "PadRight")
+];
+
+// one sample of a controller, the stick is in screen orientation with y pointing down
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct PadState {
+    pub stick: Vec2,
+    buttons: u16
+}
+
+impl PadState {
+    pub fn new(stick: Vec2, pressed: &[PadButton]) -> PadState {
+        let mut state = PadState { stick, buttons: 0 };
+
+        for button in pressed {
+            state.buttons |= 1 << *button as u16;
+        }
+
+        state
+    }
+
+    pub fn is_down(&self, button: PadButton) -> bool {
+        self.buttons & (1 << button as u16) != 0
+    }
+
+    // buttons that are down now but weren't in `previous`
+    pub fn is_pressed_since(&self, previous: &PadState, button: PadButton) -> bool {
+        self.is_down(button) && !previous.is_down(button)
+    }
+}
+
+// Where controller state comes from. The game polls it once per frame, so a virtual pad only has to hand out
+// the states it wants the game to see.
+pub trait ControllerSource {
+    // state of the first connected pad, None when nothing is plugged in
+    fn poll(&mut self) -> Option<PadState>;
+}
+
+pub struct NoController;
+
+impl ControllerSource for NoController {
+    fn poll(&mut self) -> Option<PadState> {
+        None
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use gilrs::{Axis, Button, Gilrs};
+    use macroquad::logging::error;
+    use macroquad::math::Vec2;
+
+    use super::*;
+
+    const GILRS_BUTTONS: [(Button, PadButton); 14] = [
+        (Button::South, PadButton::South),
+        (Button::East, PadButton::East),
+        (Button::West, PadButton::West),
+        (Button::North, PadButton::North),
+        (Button::LeftTrigger, PadButton::LeftBumper),
+        (Button::RightTrigger, PadButton::RightBumper),
+        (Button::LeftTrigger2, PadButton::LeftTrigger),
+        (Button::RightTrigger2, PadButton::RightTrigger),
+        (Button::Select, PadButton::Select),
+        (Button::Start, PadButton::Start),
+        (Button::DPadUp, PadButton::DPadUp),
+        (Button::DPadDown, PadButton::DPadDown),
+        (Button::DPadLeft, PadButton::DPadLeft),
+        (Button::DPadRight, PadButton::DPadRight)
+    ];
+
+    pub struct GilrsController {
+        gilrs: Gilrs
+    }
+
+    impl ControllerSource for GilrsController {
+        fn poll(&mut self) -> Option<PadState> {
+            // gilrs only updates its cached gamepad state while events are drained
+            while self.gilrs.next_event().is_some() {}
+
+            let (_, gamepad) = self.gilrs.gamepads().next()?;
+
+            let stick = Vec2::new(gamepad.value(Axis::LeftStickX), -gamepad.value(Axis::LeftStickY));
+            let pressed: Vec<PadButton> = GILRS_BUTTONS.iter()
+                .filter(|(button, _)| gamepad.is_pressed(*button))
+                .map(|(_, pad_button)| *pad_button)
+                .collect();
+
+            Some(PadState::new(stick, &pressed))
+        }
+    }
+
+    pub fn default_controller_source() -> Box<dyn ControllerSource> {
+        match Gilrs::new() {
+            Ok(gilrs) => Box::new(GilrsController { gilrs }),
+            Err(err) => {
+                error!("gamepads unavailable: {err}");
+                Box::new(NoController)
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use macroquad::math::Vec2;
+    use sapp_jsutils::JsObject;
+
+    use super::*;
+
+    unsafe extern "C" {
+        fn _gamepad_poll() -> JsObject;
+    }
+
+    // reads navigator.getGamepads() through plugin.js, buttons arrive in the standard mapping order
+    pub struct BrowserController;
+
+    const STANDARD_MAPPING: [(u32, PadButton); 14] = [
+        (0, PadButton::South),
+        (1, PadButton::East),
+        (2, PadButton::West),
+        (3, PadButton::North),
+        (4, PadButton::LeftBumper),
+        (5, PadButton::RightBumper),
+        (6, PadButton::LeftTrigger),
+        (7, PadButton::RightTrigger),
+        (8, PadButton::Select),
+        (9, PadButton::Start),
+        (12, PadButton::DPadUp),
+        (13, PadButton::DPadDown),
+        (14, PadButton::DPadLeft),
+        (15, PadButton::DPadRight)
+    ];
+
+    impl ControllerSource for BrowserController {
+        fn poll(&mut self) -> Option<PadState> {
+            let state = unsafe { _gamepad_poll() };
+
+            if state.is_nil() {
+                return None;
+            }
+
+            let stick = Vec2::new(state.field_f32("x"), state.field_f32("y"));
+            let buttons = state.field_u32("buttons");
+            let pressed: Vec<PadButton> = STANDARD_MAPPING.iter()
+                .filter(|(index, _)| buttons & (1 << index) != 0)
+                .map(|(_, pad_button)| *pad_button)
+                .collect();
+
+            Some(PadState::new(stick, &pressed))
+        }
+    }
+
+    pub fn default_controller_source() -> Box<dyn ControllerSource> {
+        Box::new(BrowserController)
+    }
+}
+
+pub use platform::*;
@@ -0,0 +1,128 @@
+use macroquad::input::{touches, Touch, TouchPhase};
+use macroquad::math::*;
+use macroquad::miniquad::window::screen_size;
+
+// pixels the stick has to be dragged before it changes the aim
+const STICK_DEAD_ZONE: f32 = 12.0;
+pub const STICK_RADIUS: f32 = 80.0;
+
+pub const PAUSE_BUTTON_SIZE: f32 = 72.0;
+const PAUSE_BUTTON_MARGIN: f32 = 16.0;
+
+pub const JUMP_BUTTON_RADIUS: f32 = 64.0;
+
+struct Stick {
+    id: u64,
+    origin: Vec2,
+    position: Vec2
+}
+
+// Left half of the screen is a floating stick that appears where the finger lands, anything touching the
+// right half holds jump. Each finger is tracked by id so aiming and jumping work at the same time.
+pub struct TouchControls {
+    stick: Option<Stick>,
+    direction: Option<Vec2>,
+    jump: bool,
+    pause: bool
+}
+
+impl TouchControls {
+    pub fn new() -> TouchControls {
+        TouchControls {
+            stick: None,
+            direction: None,
+            jump: false,
+            pause: false
+        }
+    }
+
+    // returns whether any finger is on the screen
+    pub fn update(&mut self) -> bool {
+        let touches = touches();
+        let screen = Vec2::from(screen_size());
+
+        self.pause = touches.iter()
+            .any(|touch| touch.phase == TouchPhase::Started && pause_button_bounds().contains(touch.position));
+
+        self.update_stick(&touches);
+
+        let stick_id = self.stick.as_ref().map(|stick| stick.id);
+
+        self.jump = touches.iter().any(|touch| {
+            Some(touch.id) != stick_id
+                && is_held(touch)
+                && touch.position.x >= screen.x / 2.0
+                && !pause_button_bounds().contains(touch.position)
+        });
+
+        if let Some(stick) = &self.stick {
+            let drag = stick.position - stick.origin;
+
+            if drag.length() > STICK_DEAD_ZONE {
+                // aim is measured in screen units, so the drag is scaled the same way the cursor is
+                self.direction = Some((drag / screen).normalize());
+            }
+        }
+
+        !touches.is_empty()
+    }
+
+    fn update_stick(&mut self, touches: &[Touch]) {
+        if let Some(stick) = &mut self.stick {
+            match touches.iter().find(|touch| touch.id == stick.id) {
+                Some(touch) if is_held(touch) => stick.position = touch.position,
+                _ => self.stick = None
+            }
+        }
+
+        if self.stick.is_none() {
+            let screen_width = screen_size().0;
+
+            self.stick = touches.iter()
+                .find(|touch| touch.phase == TouchPhase::Started && touch.position.x < screen_width / 2.0)
+                .map(|touch| Stick { id: touch.id, origin: touch.position, position: touch.position });
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.direction = None;
+    }
+
+    // last direction the stick was pushed, kept after the finger lifts so the aim doesn't snap back
+    pub fn direction(&self) -> Option<Vec2> {
+        self.direction
+    }
+
+    pub fn is_jump_down(&self) -> bool {
+        self.jump
+    }
+
+    pub fn is_pause_pressed(&self) -> bool {
+        self.pause
+    }
+
+    // stick base and the knob clamped to the base, in pixels
+    pub fn stick(&self) -> Option<(Vec2, Vec2)> {
+        self.stick.as_ref().map(|stick| {
+            (stick.origin, stick.origin + (stick.position - stick.origin).clamp_length_max(STICK_RADIUS))
+        })
+    }
+}
+
+fn is_held(touch: &Touch) -> bool {
+    touch.phase != TouchPhase::Ended && touch.phase != TouchPhase::Cancelled
+}
+
+pub fn pause_button_bounds() -> Rect {
+    Rect::new(
+        screen_size().0 - PAUSE_BUTTON_SIZE - PAUSE_BUTTON_MARGIN,
+        PAUSE_BUTTON_MARGIN,
+        PAUSE_BUTTON_SIZE,
+        PAUSE_BUTTON_SIZE)
+}
+
+pub fn jump_button_center() -> Vec2 {
+    let (width, height) = screen_size();
+
+    Vec2::new(width - JUMP_BUTTON_RADIUS * 2.0, height - JUMP_BUTTON_RADIUS * 2.0)
+}
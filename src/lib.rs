@@ -0,0 +1,3 @@
+// The headless game core, shared by the game binary and the tests. Nothing in here opens a window.
+pub mod simulation;
+pub mod math;
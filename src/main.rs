@@ -1,9 +1,14 @@
 mod game;
-mod math;
 mod timer;
+mod calendar;
 mod client;
+mod storage;
+mod focus;
+mod settings;
+mod input;
 
 use game::*;
+use speedy_jumper::{math, simulation};
 
 #[macroquad::main(window_config)]
 async fn main() {
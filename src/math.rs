@@ -31,7 +31,7 @@ impl Bounds2D {
     }
 
     pub fn get_center(&self) -> Vec2 {
-        return self.top_left + self.size / 2.0;
+        self.top_left + self.size / 2.0
     }
 
     pub fn get_size(&self) -> Vec2 {
@@ -0,0 +1,76 @@
+use macroquad::logging::error;
+use serde::{Deserialize, Serialize};
+
+use crate::input::{AimMode, Bindings};
+use crate::simulation::Difficulty;
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub muted: bool,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub show_fps: bool,
+    pub hud_scale: f32,
+    pub trajectory_preview: bool,
+    pub show_reticle: bool,
+    pub difficulty: Difficulty,
+    pub aim_mode: AimMode,
+    pub bindings: Bindings
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            muted: false,
+            fullscreen: false,
+            vsync: true,
+            show_fps: true,
+            hud_scale: 1.0,
+            trajectory_preview: false,
+            show_reticle: true,
+            difficulty: Difficulty::Normal,
+            aim_mode: AimMode::Mouse,
+            bindings: Bindings::default()
+        }
+    }
+}
+
+impl Settings {
+    // falls back to the defaults when nothing is saved yet or the file can't be parsed
+    pub fn load() -> Settings {
+        let Some(bytes) = storage::read(SETTINGS_FILE) else {
+            return Settings::default();
+        };
+
+        match toml::from_str(&String::from_utf8_lossy(&bytes)) {
+            Ok(settings) => settings,
+            Err(error) => {
+                error!("failed to parse {SETTINGS_FILE}, using defaults: {error}");
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = toml::to_string(self).map_err(|error| error.to_string())?;
+        storage::write(SETTINGS_FILE, text.as_bytes())
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        if self.muted { 0.0 } else { self.master_volume * self.music_volume }
+    }
+
+    pub fn effects_volume(&self) -> f32 {
+        if self.muted { 0.0 } else { self.master_volume * self.effects_volume }
+    }
+}
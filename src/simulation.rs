@@ -0,0 +1,200 @@
+use std::vec::Vec;
+
+use macroquad::math::*;
+use macroquad::rand::RandGenerator;
+
+mod player;
+mod collectable;
+mod replay;
+mod config;
+mod difficulty;
+
+pub use player::*;
+pub use collectable::*;
+pub use replay::*;
+pub use config::*;
+pub use difficulty::*;
+
+use crate::math::Bounds2D;
+
+// Everything in this module is pure gameplay: no window, GL context, audio device or wall clock.
+// The front end feeds it an InputFrame per tick and reacts to the WorldEvents it returns.
+
+pub const SIMULATION_TIMESTEP: f64 = 1.0 / 120.0;
+
+pub static SPRITE_LARGE_VIEW_RADIUS: f32 = 600.0 / 1280.0;
+pub static SPRITE_SMALL_VIEW_RADIUS: f32 = 100.0 / 720.0;
+
+#[derive(Clone, Copy, Default)]
+pub struct InputFrame {
+    pub aim: Vec2,
+    pub jump: bool
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WorldEvent {
+    Jumped, 
+    BorderBounce, 
+    BoostHit { hurtful: bool }, 
+    Died
+}
+
+pub struct World {
+    pub player: Player,
+    pub jump_boosts: Vec<JumpBoost>,
+    pub is_dead: bool,
+    rng: RandGenerator,
+    seed: u64,
+    time: f64, 
+    time_survived: f64,
+    last_spawn_time: f64,
+    events: Vec<WorldEvent>,
+    config: GameConfig
+}
+
+impl World {
+    // the same seed, config and sequence of InputFrames always produce the same run
+    pub fn new(seed: u64, config: &GameConfig) -> World {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+
+        World {
+            player: Player::new(
+                Vec2::new(0.5, 0.5),
+                Vec2::new(150.0 / 1280.0, 150.0 / 720.0), 
+                SPRITE_LARGE_VIEW_RADIUS, 
+                config.player.clone()),
+            jump_boosts: Vec::new(),
+            is_dead: false,
+            rng,
+            seed,
+            time: 0.0,
+            time_survived: 0.0,
+            last_spawn_time: 0.0,
+            events: Vec::new(),
+            config: config.clone()
+        }
+    }
+
+    // swaps the tuning mid run, used by config hot reloading
+    pub fn set_config(&mut self, config: &GameConfig) {
+        self.player.set_config(config.player.clone());
+        self.config = config.clone();
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // seconds of simulation time the player has stayed alive, frozen once they die
+    pub fn time_survived(&self) -> f64 {
+        self.time_survived
+    }
+
+    pub fn step(&mut self, input: &InputFrame) -> &[WorldEvent] {
+        self.events.clear();
+
+        self.time += SIMULATION_TIMESTEP;
+
+        if !self.is_dead {
+            self.time_survived = self.time;
+        }
+
+        self.update_entities(input);
+        self.resolve_collisions();
+
+        self.cleanup_boosts();
+        self.spawn_boosts();
+
+        if !self.is_dead && !self.player.get_bounds().intersects(screen_bounds()) {
+            self.is_dead = true;
+            self.events.push(WorldEvent::Died);
+        }
+
+        &self.events
+    }
+
+    fn update_entities(&mut self, input: &InputFrame) {
+        for boost in &mut self.jump_boosts {
+            boost.update(SIMULATION_TIMESTEP);
+        }
+
+        if !self.is_dead {
+            self.player.update(input, SIMULATION_TIMESTEP, &mut self.events);
+        }
+    }
+
+    fn resolve_collisions(&mut self) {
+        if self.is_dead {
+            return;
+        }
+
+        for boost in &self.jump_boosts {
+            if boost.bounds.intersects(self.player.get_bounds()) {
+                if boost.hurtful {
+                    self.player.boost_counter -= self.config.boosts.red_penalty;
+                    self.player.view_radius = self.config.player.penalty_view_radius;
+                }
+                else {
+                    self.player.boost_counter += self.config.boosts.green_reward;
+                    self.player.view_radius = SPRITE_LARGE_VIEW_RADIUS;
+                }
+
+                self.events.push(WorldEvent::BoostHit { hurtful: boost.hurtful });
+            }
+        }
+
+        self.player.boost_counter = self.player.boost_counter.max(0);
+    }
+
+    fn cleanup_boosts(&mut self) {
+        let screen_bounds = screen_bounds();
+        let player_bounds = self.player.get_bounds();
+
+        self.jump_boosts.retain(|boost| boost.bounds.intersects(screen_bounds) && !boost.bounds.intersects(player_bounds));
+    }
+
+    fn random_boost_acceleration(&self) -> Vec2 {
+        let mut upper_bound = clamp(1.0 - 1.0 / (self.time * self.config.boosts.acceleration_ramp), 0.2, 1.0) as f32;
+        upper_bound *= self.config.boosts.acceleration_scale;
+
+        Vec2::new(self.rng.gen_range(-upper_bound, upper_bound), self.rng.gen_range(-upper_bound, upper_bound))
+    }
+
+    fn gen_random_boost(&self) -> JumpBoost {
+        loop {
+            let boost_position = Vec2::new(self.rng.gen_range(0.0, 1.0), self.rng.gen_range(0.0, 1.0));
+            let boost_size = Vec2::splat(self.rng.gen_range(25.0 / 1280.0, 50.0 / 1280.0));
+            
+            if !Bounds2D::new(boost_position, boost_size).intersects(self.player.get_bounds()) {
+                break JumpBoost::new(
+                        boost_position, 
+                        boost_size, 
+                        self.rng.gen_range(0.0_f64, 1.0_f64) >= 1.0 - self.config.boosts.red_chance, 
+                        self.random_boost_acceleration())
+            }
+        }
+    }   
+
+    fn spawn_boosts(&mut self) {
+        let max_boosts = self.config.boosts.max_boosts;
+        let max_boosts_add = self.config.boosts.max_boosts_add;
+
+        if self.jump_boosts.len() >= max_boosts || self.time - self.last_spawn_time < self.config.boosts.spawn_cooldown {
+            return;
+        }
+
+        self.last_spawn_time = self.time;
+
+        let boosts_to_add = max_boosts_add.min(max_boosts - self.jump_boosts.len());
+
+        for _ in 0..boosts_to_add {
+            let boost = self.gen_random_boost();
+            self.jump_boosts.push(boost);
+        }
+    }
+}
+
+pub fn screen_bounds() -> Bounds2D {
+    Bounds2D::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0))
+}
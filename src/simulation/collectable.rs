@@ -1,13 +1,12 @@
 use macroquad::math::*;
-use macroquad::prelude::*;
 
-use crate::game::draw_rectangle_screen;
 use crate::math::*;
 
 
 pub struct JumpBoost {
     pub bounds: Bounds2D, 
     pub hurtful: bool,
+    previous_position: Vec2,
     velocity: Vec2, 
     acceleration: Vec2
 }
@@ -17,21 +16,19 @@ impl JumpBoost {
         JumpBoost { 
             bounds: Bounds2D::new(position, size), 
             hurtful,
+            previous_position: position,
             velocity: Vec2::new(0.0, 0.0), 
             acceleration
         }
     }
 
+    pub fn get_interpolated_bounds(&self, alpha: f32) -> Bounds2D {
+        Bounds2D::new(self.previous_position.lerp(self.bounds.get_position(), alpha), self.bounds.get_size())
+    }
+
     pub fn update(&mut self, delta_time: f64) {
+        self.previous_position = self.bounds.get_position();
         self.velocity += self.acceleration * delta_time as f32;
         self.bounds.translate(self.velocity * delta_time as f32);
     }
-
-    pub fn draw(&self) {
-        if self.hurtful {
-            draw_rectangle_screen(self.bounds.get_position(), self.bounds.get_size(), RED); 
-        } else {
-            draw_rectangle_screen(self.bounds.get_position(), self.bounds.get_size(), GREEN);
-        }
-    }
 }
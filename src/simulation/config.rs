@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::SPRITE_SMALL_VIEW_RADIUS;
+
+// Every gameplay tuning value in one place. Missing keys fall back to the defaults below, unknown keys are
+// rejected so a typo in the file doesn't silently do nothing.
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerConfig {
+    pub gravity: f32,
+    // seconds between jumps
+    pub boost_cooldown: f64,
+    pub boost_speed_increase: f32,
+    pub max_velocity: f32,
+    // how much of the current speed a wall bounce pushes back with
+    pub wall_force_multiplier: f32,
+    pub starting_boosts: i32,
+    // view radius after hitting a red boost
+    pub penalty_view_radius: f32
+}
+
+impl Default for PlayerConfig {
+    fn default() -> PlayerConfig {
+        PlayerConfig {
+            gravity: 1.1,
+            boost_cooldown: 0.5,
+            boost_speed_increase: 1.0,
+            max_velocity: 40.0,
+            wall_force_multiplier: 1.1,
+            starting_boosts: 10,
+            penalty_view_radius: SPRITE_SMALL_VIEW_RADIUS
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoostConfig {
+    pub max_boosts: usize,
+    // most boosts spawned in one wave
+    pub max_boosts_add: usize,
+    // seconds between spawn waves
+    pub spawn_cooldown: f64,
+    pub green_reward: i32,
+    pub red_penalty: i32,
+    // chance of a spawned boost being red
+    pub red_chance: f64,
+    // boost speed limit once fully ramped up, and how fast it ramps up over the run
+    pub acceleration_scale: f32,
+    pub acceleration_ramp: f64
+}
+
+impl Default for BoostConfig {
+    fn default() -> BoostConfig {
+        BoostConfig {
+            max_boosts: 15,
+            max_boosts_add: 7,
+            spawn_cooldown: 1.0,
+            green_reward: 2,
+            red_penalty: 1,
+            red_chance: 0.5,
+            acceleration_scale: 1.0,
+            acceleration_ramp: 0.05
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    // seconds between background stage changes
+    pub stage_interval: f64,
+    pub player: PlayerConfig,
+    pub boosts: BoostConfig
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            stage_interval: 20.0,
+            player: PlayerConfig::default(),
+            boosts: BoostConfig::default()
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn from_toml(text: &str) -> Result<GameConfig, String> {
+        let config: GameConfig = toml::from_str(text).map_err(|error| error.to_string())?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // FNV-1a over the config as JSON, the same on every platform and build unlike std's hasher
+    pub fn fingerprint(&self) -> u64 {
+        let text = serde_json::to_string(self).unwrap_or_default();
+
+        text.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let player = &self.player;
+        let boosts = &self.boosts;
+
+        check_finite("stage_interval", self.stage_interval, self.stage_interval > 0.0, "greater than 0")?;
+
+        check_finite("player.gravity", player.gravity as f64, player.gravity >= 0.0, "at least 0")?;
+        check_finite("player.boost_cooldown", player.boost_cooldown, player.boost_cooldown >= 0.0, "at least 0")?;
+        check_finite("player.boost_speed_increase", player.boost_speed_increase as f64, player.boost_speed_increase > 0.0, "greater than 0")?;
+        check_finite("player.max_velocity", player.max_velocity as f64, player.max_velocity > 0.0, "greater than 0")?;
+        check_finite("player.wall_force_multiplier", player.wall_force_multiplier as f64, player.wall_force_multiplier >= 0.0, "at least 0")?;
+        check("player.starting_boosts", player.starting_boosts, player.starting_boosts >= 0, "at least 0")?;
+        check_finite("player.penalty_view_radius", player.penalty_view_radius as f64, player.penalty_view_radius > 0.0, "greater than 0")?;
+
+        check("boosts.max_boosts", boosts.max_boosts, boosts.max_boosts >= 1, "at least 1")?;
+        check("boosts.max_boosts_add", boosts.max_boosts_add, boosts.max_boosts_add >= 1, "at least 1")?;
+        check_finite("boosts.spawn_cooldown", boosts.spawn_cooldown, boosts.spawn_cooldown >= 0.0, "at least 0")?;
+        check("boosts.green_reward", boosts.green_reward, boosts.green_reward >= 0, "at least 0")?;
+        check("boosts.red_penalty", boosts.red_penalty, boosts.red_penalty >= 0, "at least 0")?;
+        check_finite("boosts.red_chance", boosts.red_chance, (0.0..=1.0).contains(&boosts.red_chance), "between 0 and 1")?;
+        check_finite("boosts.acceleration_scale", boosts.acceleration_scale as f64, boosts.acceleration_scale >= 0.0, "at least 0")?;
+        check_finite("boosts.acceleration_ramp", boosts.acceleration_ramp, boosts.acceleration_ramp > 0.0, "greater than 0")?;
+
+        Ok(())
+    }
+}
+
+fn check<T: std::fmt::Display>(name: &str, value: T, valid: bool, requirement: &str) -> Result<(), String> {
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("{name} must be {requirement}, got {value}"))
+    }
+}
+
+fn check_finite(name: &str, value: f64, valid: bool, requirement: &str) -> Result<(), String> {
+    check(name, value, valid && value.is_finite(), requirement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_keep_their_defaults() {
+        let config = GameConfig::from_toml("[player]\ngravity = 2.5").unwrap();
+
+        assert_eq!(config.player.gravity, 2.5);
+        assert_eq!(config.player.max_velocity, PlayerConfig::default().max_velocity);
+        assert_eq!(config.boosts, BoostConfig::default());
+        assert_eq!(GameConfig::from_toml("").unwrap(), GameConfig::default());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(GameConfig::from_toml("stage_intreval = 5.0").is_err());
+        assert!(GameConfig::from_toml("[player]\ngravty = 1.0").is_err());
+    }
+
+    #[test]
+    fn out_of_range_values_name_the_field() {
+        let error = GameConfig::from_toml("[boosts]\nspawn_cooldown = -1.0").unwrap_err();
+
+        assert_eq!(error, "boosts.spawn_cooldown must be at least 0, got -1");
+
+        let error = GameConfig::from_toml("[player]\ngravity = nan").unwrap_err();
+
+        assert!(error.starts_with("player.gravity must be at least 0"), "{error}");
+
+        assert!(GameConfig::from_toml("[boosts]\nred_chance = 1.5").is_err());
+        assert!(GameConfig::from_toml("stage_interval = inf").is_err());
+    }
+
+    #[test]
+    fn any_change_changes_the_fingerprint() {
+        let config = GameConfig::default();
+        let mut retuned = config.clone();
+
+        retuned.boosts.red_penalty += 1;
+
+        assert_eq!(config.fingerprint(), GameConfig::default().fingerprint());
+        assert_ne!(config.fingerprint(), retuned.fingerprint());
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::GameConfig;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane
+}
+
+pub const DIFFICULTIES: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Insane];
+
+// Multipliers on top of the GameConfig, so Normal is exactly what the config file says and retuning it
+// moves every preset along with it.
+struct DifficultyModifiers {
+    spawn: f32,
+    acceleration: f32,
+    acceleration_ramp: f64,
+    red_chance: f64,
+    starting_boosts: f32,
+    penalty_view_radius: f32
+}
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Insane => "Insane"
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        let index = DIFFICULTIES.iter().position(|difficulty| difficulty == self).unwrap_or(0);
+
+        DIFFICULTIES[(index + 1) % DIFFICULTIES.len()]
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Insane => 3
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Difficulty> {
+        match byte {
+            0 => Some(Difficulty::Easy),
+            1 => Some(Difficulty::Normal),
+            2 => Some(Difficulty::Hard),
+            3 => Some(Difficulty::Insane),
+            _ => None
+        }
+    }
+
+    fn modifiers(&self) -> DifficultyModifiers {
+        match self {
+            Difficulty::Easy => DifficultyModifiers {
+                spawn: 0.7,
+                acceleration: 0.7,
+                acceleration_ramp: 0.7,
+                red_chance: 0.7,
+                starting_boosts: 1.5,
+                penalty_view_radius: 1.5
+            },
+            Difficulty::Normal => DifficultyModifiers {
+                spawn: 1.0,
+                acceleration: 1.0,
+                acceleration_ramp: 1.0,
+                red_chance: 1.0,
+                starting_boosts: 1.0,
+                penalty_view_radius: 1.0
+            },
+            Difficulty::Hard => DifficultyModifiers {
+                spawn: 1.3,
+                acceleration: 1.3,
+                acceleration_ramp: 1.5,
+                red_chance: 1.2,
+                starting_boosts: 0.8,
+                penalty_view_radius: 0.75
+            },
+            Difficulty::Insane => DifficultyModifiers {
+                spawn: 1.7,
+                acceleration: 1.7,
+                acceleration_ramp: 2.5,
+                red_chance: 1.4,
+                starting_boosts: 0.5,
+                penalty_view_radius: 0.5
+            }
+        }
+    }
+
+    pub fn apply(&self, config: &GameConfig) -> GameConfig {
+        let modifiers = self.modifiers();
+        let mut config = config.clone();
+
+        let scale = |value: usize| ((value as f32 * modifiers.spawn).round() as usize).max(1);
+
+        config.boosts.max_boosts = scale(config.boosts.max_boosts);
+        config.boosts.max_boosts_add = scale(config.boosts.max_boosts_add);
+        config.boosts.acceleration_scale *= modifiers.acceleration;
+        config.boosts.acceleration_ramp *= modifiers.acceleration_ramp;
+        config.boosts.red_chance = (config.boosts.red_chance * modifiers.red_chance).min(1.0);
+
+        config.player.starting_boosts = (config.player.starting_boosts as f32 * modifiers.starting_boosts).round() as i32;
+        config.player.penalty_view_radius *= modifiers.penalty_view_radius;
+
+        config
+    }
+}
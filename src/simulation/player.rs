@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use macroquad::math::*;
+
+use crate::math::*;
+use crate::simulation::{InputFrame, PlayerConfig, WorldEvent};
+
+#[derive(Clone)]
+pub struct Player {
+    pub boost_counter: i32,
+    pub view_radius: f32,
+
+    position: Vec2, 
+    previous_position: Vec2,
+    size: Vec2,
+
+    velocity: Vec2,
+
+    boost_cooldown_remaining: Duration,
+    config: PlayerConfig
+}
+
+impl Player { 
+    pub fn new(position: Vec2, size: Vec2, starting_view_radius: f32, config: PlayerConfig) -> Self {
+        Player {
+            boost_counter: config.starting_boosts,
+            view_radius: starting_view_radius,
+            position,
+            previous_position: position,
+            size, 
+
+            velocity: Vec2::new(0.0, 0.0),
+
+            boost_cooldown_remaining: Duration::from_secs_f64(config.boost_cooldown),
+            config
+        }
+    }
+
+    pub fn set_config(&mut self, config: PlayerConfig) {
+        self.config = config;
+    }
+
+    pub fn get_bounds(&self) -> Bounds2D {
+        Bounds2D::new(self.position, self.size)
+    }
+
+    pub fn get_interpolated_bounds(&self, alpha: f32) -> Bounds2D {
+        Bounds2D::new(self.previous_position.lerp(self.position, alpha), self.size)
+    }
+
+    // 0 right after a jump, 1 once the next jump is ready
+    pub fn cooldown_progress(&self) -> f32 {
+        if self.config.boost_cooldown <= 0.0 {
+            return 1.0;
+        }
+
+        1.0 - (self.boost_cooldown_remaining.as_secs_f64() / self.config.boost_cooldown).min(1.0) as f32
+    }
+
+    // Runs the real update on a copy, so the predicted centers follow exactly the path the next jump towards
+    // `aim` would take. Empty when there are no boosts left to jump with.
+    pub fn predict_jump(&self, aim: Vec2, delta_time: f64, ticks: usize) -> Vec<Vec2> {
+        if self.boost_counter <= 0 {
+            return Vec::new();
+        }
+
+        let mut player = self.clone();
+        let mut events = Vec::new();
+
+        player.boost_cooldown_remaining = Duration::ZERO;
+
+        (0..ticks)
+            .map(|tick| {
+                player.update(&InputFrame { aim, jump: tick == 0 }, delta_time, &mut events);
+                player.get_bounds().get_center()
+            })
+            .collect()
+    }
+
+    fn handle_movement(&mut self, input: &InputFrame, events: &mut Vec<WorldEvent>) {
+        let direction = input.aim.normalize_or(Vec2::new(0.0, 0.0));
+
+        if direction.length() == 0.0 {
+            return;
+        }
+
+        let mut scalar = 0.0;
+
+        if input.jump && self.boost_cooldown_remaining.is_zero() && self.boost_counter > 0 {
+            scalar += self.config.boost_speed_increase;
+
+            self.boost_cooldown_remaining = Duration::from_secs_f64(self.config.boost_cooldown);
+            self.boost_counter -= 1;
+
+            events.push(WorldEvent::Jumped);
+        }   
+
+        self.velocity += direction * scalar;
+    }
+
+    fn handle_gravity(&mut self, delta_time: f64) {
+        self.velocity += Vec2::new(0.0, self.config.gravity * delta_time as f32);
+    }
+
+    fn handle_border(&mut self, events: &mut Vec<WorldEvent>) {
+        let player_bounds = self.get_bounds();
+
+        let mut force = Vec2::new(0.0, 0.0);
+
+        if player_bounds.get_position().x == 0.0 {
+            force += Vec2::new(1.0, 0.0);
+        } else if player_bounds.get_position().x == 1.0 - self.size.x {
+            force += Vec2::new(-1.0, 0.0);
+        }
+
+        if player_bounds.get_position().y == 0.0 {
+            force += Vec2::new(0.0, 1.0);
+        }
+
+        if force.length() > 0.0 {
+            events.push(WorldEvent::BorderBounce);
+        }
+
+        force = force.normalize_or_zero() * self.velocity.length() * self.config.wall_force_multiplier; 
+        self.velocity += force; //* delta_time as f32;
+    }
+
+    pub fn update(&mut self, input: &InputFrame, delta_time: f64, events: &mut Vec<WorldEvent>) {
+        self.previous_position = self.position;
+        self.boost_cooldown_remaining = self.boost_cooldown_remaining.saturating_sub(Duration::from_secs_f64(delta_time));
+
+        self.handle_movement(input, events);
+        self.handle_gravity(delta_time);
+        self.handle_border(events);
+
+        self.velocity = self.velocity.clamp_length(0.0, self.config.max_velocity);
+        self.position += self.velocity * delta_time as f32;
+        self.velocity = self.velocity.lerp(Vec2::new(0.0, 0.0), 0.1 * delta_time as f32);
+
+        self.position = self.position.clamp(
+            Vec2::new(0.0, 0.0), 
+            Vec2::new(1.0 - self.size.x, f32::MAX),
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use macroquad::math::*;
+
+use crate::simulation::{Difficulty, GameConfig, InputFrame};
+
+// Replay file layout, all values little endian:
+//   magic "SJRP", version u16, seed u64, difficulty u8, config fingerprint u64, frame count u32, run count u32
+//   followed by runs of identical frames: repeat u16, aim x f32, aim y f32, jump u8
+const REPLAY_MAGIC: &[u8; 4] = b"SJRP";
+const REPLAY_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 4 + 2 + 8 + 1 + 8 + 4 + 4;
+const RUN_SIZE: usize = 2 + 4 + 4 + 1;
+
+#[derive(Clone)]
+pub struct Replay {
+    seed: u64,
+    difficulty: Difficulty,
+    // of the config the run was played with, difficulty applied; any other config plays it back differently
+    config_fingerprint: u64,
+    frames: Vec<InputFrame>
+}
+
+impl Replay {
+    // `config` is the one the World runs with, so difficulty already applied
+    pub fn new(seed: u64, difficulty: Difficulty, config: &GameConfig) -> Replay {
+        Replay {
+            seed,
+            difficulty,
+            config_fingerprint: config.fingerprint(),
+            frames: Vec::new()
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn config_fingerprint(&self) -> u64 {
+        self.config_fingerprint
+    }
+
+    pub fn frames(&self) -> &[InputFrame] {
+        &self.frames
+    }
+
+    pub fn record(&mut self, frame: InputFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut runs: Vec<(u16, InputFrame)> = Vec::new();
+
+        for frame in &self.frames {
+            match runs.last_mut() {
+                Some((repeat, last)) if *repeat < u16::MAX && same_frame(last, frame) => *repeat += 1,
+                _ => runs.push((1, *frame))
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + runs.len() * RUN_SIZE);
+
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.difficulty.to_byte());
+        bytes.extend_from_slice(&self.config_fingerprint.to_le_bytes());
+        bytes.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+
+        for (repeat, frame) in runs {
+            bytes.extend_from_slice(&repeat.to_le_bytes());
+            bytes.extend_from_slice(&frame.aim.x.to_le_bytes());
+            bytes.extend_from_slice(&frame.aim.y.to_le_bytes());
+            bytes.push(frame.jump as u8);
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, String> {
+        if bytes.len() < 6 || &bytes[0..4] != REPLAY_MAGIC {
+            return Err("not a replay file".to_owned());
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+
+        if version != REPLAY_VERSION {
+            return Err(format!("unsupported replay version {version}"));
+        }
+
+        if bytes.len() < HEADER_SIZE {
+            return Err("replay file is truncated".to_owned());
+        }
+
+        let seed = u64::from_le_bytes(bytes[6..14].try_into().unwrap());
+        let difficulty = Difficulty::from_byte(bytes[14]).ok_or("replay has an unknown difficulty")?;
+
+        let config_fingerprint = u64::from_le_bytes(bytes[15..23].try_into().unwrap());
+
+        let frame_count = u32::from_le_bytes(bytes[23..27].try_into().unwrap()) as usize;
+        let run_count = u32::from_le_bytes(bytes[27..31].try_into().unwrap()) as usize;
+
+        let runs = &bytes[HEADER_SIZE..];
+
+        if run_count.checked_mul(RUN_SIZE) != Some(runs.len()) {
+            return Err("replay file is truncated".to_owned());
+        }
+
+        // sized from the runs actually in the file, the header's frame count is only checked against them
+        let decoded_count: usize = runs.chunks_exact(RUN_SIZE)
+            .map(|run| u16::from_le_bytes([run[0], run[1]]) as usize)
+            .sum();
+
+        if decoded_count != frame_count {
+            return Err("replay frame count does not match its header".to_owned());
+        }
+
+        let mut frames = Vec::with_capacity(decoded_count);
+
+        for run in runs.chunks_exact(RUN_SIZE) {
+            let repeat = u16::from_le_bytes([run[0], run[1]]);
+            let frame = InputFrame {
+                aim: Vec2::new(
+                    f32::from_le_bytes(run[2..6].try_into().unwrap()), 
+                    f32::from_le_bytes(run[6..10].try_into().unwrap())),
+                jump: run[10] != 0
+            };
+
+            frames.extend(std::iter::repeat_n(frame, repeat as usize));
+        }
+
+        Ok(Replay { seed, difficulty, config_fingerprint, frames })
+    }
+}
+
+// compares bit patterns so a decoded replay feeds the simulation exactly what was recorded
+fn same_frame(a: &InputFrame, b: &InputFrame) -> bool {
+    a.aim.x.to_bits() == b.aim.x.to_bits() && a.aim.y.to_bits() == b.aim.y.to_bits() && a.jump == b.jump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded() -> Replay {
+        let mut replay = Replay::new(u64::MAX - 3, Difficulty::Hard, &Difficulty::Hard.apply(&GameConfig::default()));
+
+        for tick in 0..300_u32 {
+            replay.record(InputFrame {
+                aim: Vec2::new((tick / 50) as f32 * 0.1, -1.0),
+                jump: tick.is_multiple_of(40)
+            });
+        }
+
+        replay
+    }
+
+    fn set_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn replays_survive_a_round_trip() {
+        let replay = recorded();
+        let bytes = replay.to_bytes();
+        let decoded = Replay::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.seed(), replay.seed());
+        assert_eq!(decoded.difficulty(), Difficulty::Hard);
+        assert_eq!(decoded.config_fingerprint(), replay.config_fingerprint());
+        assert_eq!(decoded.frames().len(), replay.frames().len());
+        assert!(decoded.frames().iter().zip(replay.frames()).all(|(a, b)| same_frame(a, b)));
+
+        // identical frames are stored once per run
+        assert!(bytes.len() < replay.frames().len() * RUN_SIZE / 4);
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        let bytes = recorded().to_bytes();
+
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Replay::from_bytes(&bytes[..HEADER_SIZE - 1]).is_err());
+        assert!(Replay::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        let mut bytes = recorded().to_bytes();
+        bytes[0..4].copy_from_slice(b"SJGH");
+
+        assert_eq!(Replay::from_bytes(&bytes).err().unwrap(), "not a replay file");
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut bytes = recorded().to_bytes();
+        bytes[4..6].copy_from_slice(&99_u16.to_le_bytes());
+
+        assert_eq!(Replay::from_bytes(&bytes).err().unwrap(), "unsupported replay version 99");
+    }
+
+    #[test]
+    fn counts_in_the_header_are_not_trusted() {
+        let bytes = recorded().to_bytes();
+        let frame_count_offset = HEADER_SIZE - 8;
+
+        let mut oversized_frames = bytes.clone();
+        set_u32(&mut oversized_frames, frame_count_offset, u32::MAX);
+
+        assert!(Replay::from_bytes(&oversized_frames).is_err());
+
+        let mut oversized_runs = bytes.clone();
+        set_u32(&mut oversized_runs, frame_count_offset + 4, u32::MAX);
+
+        assert!(Replay::from_bytes(&oversized_runs).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+// Small key/value persistence: files in the user data dir on native, localStorage on the web build.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn data_dir() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("speedy_jumper")
+    }
+
+    pub fn write(name: &str, data: &[u8]) -> Result<(), String> {
+        let path = data_dir().join(name);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+        }
+
+        fs::write(&path, data).map_err(|error| format!("failed to write {}: {error}", path.display()))
+    }
+
+    pub fn read(name: &str) -> Option<Vec<u8>> {
+        fs::read(data_dir().join(name)).ok()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use sapp_jsutils::JsObject;
+
+    unsafe extern "C" {
+        fn _storage_write(name: JsObject, data: JsObject);
+        fn _storage_read(name: JsObject) -> JsObject;
+    }
+
+    pub fn write(name: &str, data: &[u8]) -> Result<(), String> {
+        unsafe {
+            _storage_write(JsObject::string(name), JsObject::buffer(data));
+        }
+
+        Ok(())
+    }
+
+    pub fn read(name: &str) -> Option<Vec<u8>> {
+        let object = unsafe { _storage_read(JsObject::string(name)) };
+
+        if object.is_nil() {
+            return None;
+        }
+
+        let mut data = Vec::new();
+        object.to_byte_buffer(&mut data);
+
+        Some(data)
+    }
+}
+
+pub use platform::*;
@@ -1,28 +1,150 @@
-use macroquad::time::get_time;
 use std::time::Duration;
 
+// Simulation time, separate from real time: it can be paused, scaled and stepped by hand.
+pub struct GameClock {
+    time: f64,
+    time_scale: f64,
+    paused: bool
+}
+
+impl GameClock {
+    pub fn new() -> GameClock {
+        GameClock {
+            time: 0.0,
+            time_scale: 1.0,
+            paused: false
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    // feeds a real frame time into the clock and returns how much game time passed
+    pub fn advance(&mut self, real_delta_time: f64) -> f64 {
+        if self.paused {
+            return 0.0;
+        }
+
+        let delta_time = real_delta_time * self.time_scale;
+        self.time += delta_time;
+
+        delta_time
+    }
+
+    // moves the clock forward by exactly delta_time, even while paused
+    pub fn step(&mut self, delta_time: f64) -> f64 {
+        self.time += delta_time;
+        delta_time
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.time_scale = time_scale.max(0.0);
+    }
+}
+
 #[derive(Clone)]
 pub struct Timer {
-    start: Duration,
+    start: Duration
 }
 
 impl Timer {
-    pub fn new() -> Timer {
+    pub fn new(clock: &GameClock) -> Timer {
         Timer {
-            start: Duration::from_secs_f64(get_time())
+            start: Duration::from_secs_f64(clock.time())
         }
     }
 
-    pub fn has_elapsed(&self, duration: Duration) -> bool {
-        self.elapsed() >= duration
+    pub fn has_elapsed(&self, clock: &GameClock, duration: Duration) -> bool {
+        self.elapsed(clock) >= duration
     }
 
-    pub fn elapsed(&self) -> Duration {
-        Duration::from_secs_f64(get_time()) - self.start
+    pub fn elapsed(&self, clock: &GameClock) -> Duration {
+        Duration::from_secs_f64(clock.time()).saturating_sub(self.start)
     }
     
-    pub fn reset(&mut self) {
-        self.start = Duration::from_secs_f64(get_time());
+    pub fn reset(&mut self, clock: &GameClock) {
+        self.start = Duration::from_secs_f64(clock.time());
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: f64 = 1.0 / 120.0;
+
+    #[test]
+    fn timers_do_not_elapse_while_paused() {
+        let mut clock = GameClock::new();
+        let timer = Timer::new(&clock);
+
+        clock.advance(1.0);
+        clock.pause();
+
+        assert_eq!(clock.advance(5.0), 0.0);
+        assert_eq!(timer.elapsed(&clock), Duration::from_secs(1));
+
+        clock.resume();
+        clock.advance(1.0);
+
+        assert!(timer.has_elapsed(&clock, Duration::from_secs(2)));
+        assert!(!timer.has_elapsed(&clock, Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn time_scale_stretches_real_time() {
+        let mut clock = GameClock::new();
+
+        clock.set_time_scale(0.25);
+
+        assert_eq!(clock.advance(2.0), 0.5);
+        assert_eq!(clock.time(), 0.5);
+
+        clock.set_time_scale(-1.0);
+
+        assert_eq!(clock.time_scale(), 0.0);
+        assert_eq!(clock.advance(2.0), 0.0);
+    }
+
+    #[test]
+    fn stepping_advances_exactly_one_tick_while_paused() {
+        let mut clock = GameClock::new();
+        let timer = Timer::new(&clock);
+
+        clock.pause();
+
+        assert_eq!(clock.step(TICK), TICK);
+        assert_eq!(clock.time(), TICK);
+        assert_eq!(timer.elapsed(&clock), Duration::from_secs_f64(TICK));
+        assert!(clock.is_paused());
+    }
+
+    #[test]
+    fn reset_restarts_a_timer_at_the_current_time() {
+        let mut clock = GameClock::new();
+        let mut timer = Timer::new(&clock);
+
+        clock.advance(3.0);
+        timer.reset(&clock);
+        clock.advance(1.0);
+
+        assert_eq!(timer.elapsed(&clock), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,96 @@
+use macroquad::math::Vec2;
+
+use speedy_jumper::simulation::*;
+
+// ten minutes of simulation, far longer than any of these runs take
+const MAX_TICKS: usize = 120 * 60 * 10;
+
+// jumps towards a direction that changes every second
+fn scripted_input(tick: usize) -> InputFrame {
+    let directions = [Vec2::new(-1.0, -1.0), Vec2::new(0.0, -1.0), Vec2::new(1.0, -1.0), Vec2::new(0.5, -1.0)];
+
+    InputFrame {
+        aim: directions[tick / 120 % directions.len()],
+        jump: tick.is_multiple_of(30)
+    }
+}
+
+// runs until the player dies, returns every tick's events
+fn run_until_death(world: &mut World, input: impl Fn(usize) -> InputFrame) -> Vec<Vec<WorldEvent>> {
+    let mut events = Vec::new();
+
+    for tick in 0..MAX_TICKS {
+        events.push(world.step(&input(tick)).to_vec());
+
+        if world.is_dead {
+            break;
+        }
+    }
+
+    events
+}
+
+#[test]
+fn the_same_seed_and_inputs_play_the_same_run() {
+    let config = GameConfig::default();
+    let mut first = World::new(42, &config);
+    let mut second = World::new(42, &config);
+
+    for tick in 0..120 * 30 {
+        let input = scripted_input(tick);
+
+        assert_eq!(first.step(&input), second.step(&input), "events differ at tick {tick}");
+        assert_eq!(first.player.get_bounds().get_position(), second.player.get_bounds().get_position(), "player differs at tick {tick}");
+        assert_eq!(first.player.boost_counter, second.player.boost_counter);
+        assert_eq!(first.jump_boosts.len(), second.jump_boosts.len());
+    }
+
+    assert_eq!(first.time_survived(), second.time_survived());
+}
+
+#[test]
+fn different_seeds_spawn_different_boosts() {
+    let config = GameConfig::default();
+    let mut first = World::new(1, &config);
+    let mut second = World::new(2, &config);
+
+    // the first wave spawns once the spawn cooldown has passed
+    for _ in 0..120 * 2 {
+        first.step(&InputFrame::default());
+        second.step(&InputFrame::default());
+    }
+
+    let positions = |world: &World| world.jump_boosts.iter().map(|boost| boost.bounds.get_position()).collect::<Vec<_>>();
+
+    assert!(!positions(&first).is_empty());
+    assert_ne!(positions(&first), positions(&second));
+}
+
+#[test]
+fn falling_off_the_screen_dies_once() {
+    let mut world = World::new(7, &GameConfig::default());
+
+    let events = run_until_death(&mut world, |_| InputFrame::default());
+
+    assert!(world.is_dead);
+    assert_eq!(events.last().unwrap().last(), Some(&WorldEvent::Died));
+    assert_eq!(events.iter().flatten().filter(|event| **event == WorldEvent::Died).count(), 1);
+
+    // time stops counting once the player is dead
+    let time_survived = world.time_survived();
+
+    assert!(world.step(&InputFrame::default()).is_empty());
+    assert_eq!(world.time_survived(), time_survived);
+}
+
+#[test]
+fn jumping_into_a_wall_bounces() {
+    let mut world = World::new(7, &GameConfig::default());
+
+    let events = run_until_death(&mut world, |tick| InputFrame { aim: Vec2::new(-1.0, -0.2), jump: tick.is_multiple_of(60) });
+
+    let jumped = events.iter().position(|tick| tick.contains(&WorldEvent::Jumped)).expect("never jumped");
+    let bounced = events.iter().position(|tick| tick.contains(&WorldEvent::BorderBounce)).expect("never bounced");
+
+    assert!(jumped < bounced);
+}